@@ -0,0 +1,78 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use headache::parser::parse;
+use headache::run_capture;
+use headache::samples::{COMMENTED_HELLO_WORLD, HELLO_WORLD, MANDELBROT, MANDELBROT_RESULT, SHORTER_HELLO_WORLD};
+
+/// Function to compile a program to native code and run it, with an empty input and its output
+/// captured like `run_capture` does.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn jit_capture(source: &str) -> Vec<u8> {
+    let mut output = Vec::new();
+    headache::compiler::compile(source, &mut &b""[..], &mut output).unwrap().run().unwrap();
+    output
+}
+
+fn parse_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, source) in [("hello_world", HELLO_WORLD), ("commented_hello_world", COMMENTED_HELLO_WORLD), ("mandelbrot", MANDELBROT)] {
+        group.bench_function(name, |b| b.iter(|| parse(black_box(source)).unwrap()));
+    }
+    group.finish();
+}
+
+fn interpreter_benches(c: &mut Criterion) {
+    // A benchmark of a program that no longer runs right measures nothing.
+    assert_eq!(run_capture(MANDELBROT, b"").unwrap(), MANDELBROT_RESULT.as_bytes());
+
+    let mut group = c.benchmark_group("interpreter");
+    group.bench_function("shorter_hello_world", |b| b.iter(|| run_capture(black_box(SHORTER_HELLO_WORLD), b"").unwrap()));
+    group.sample_size(10);
+    group.bench_function("mandelbrot", |b| b.iter(|| run_capture(black_box(MANDELBROT), b"").unwrap()));
+    group.finish();
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn jit_benches(c: &mut Criterion) {
+    assert_eq!(jit_capture(MANDELBROT), MANDELBROT_RESULT.as_bytes());
+
+    let mut group = c.benchmark_group("jit");
+    group.bench_function("shorter_hello_world", |b| b.iter(|| jit_capture(black_box(SHORTER_HELLO_WORLD))));
+    group.sample_size(10);
+    group.bench_function("mandelbrot", |b| b.iter(|| jit_capture(black_box(MANDELBROT))));
+    group.finish();
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn jit_benches(_: &mut Criterion) {}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn jit_option_benches(c: &mut Criterion) {
+    use headache::compiler::{compile_with_options, CompileOptions, WrapMode, OUTPUT_BUFFER_SIZE};
+
+    let mut group = c.benchmark_group("jit_options");
+    group.sample_size(10);
+    for (name, wrap_mode) in [("mandelbrot_wrap", WrapMode::Wrap), ("mandelbrot_assume", WrapMode::Assume)] {
+        let options = CompileOptions { wrap_mode, ..CompileOptions::default() };
+        group.bench_function(name, |b| b.iter(|| {
+            compile_with_options(black_box(MANDELBROT), &mut &b""[..], &mut Vec::new(), options).unwrap().run().unwrap()
+        }));
+    }
+    // The output goes to a file, so every flush of the buffer is a write syscall.
+    let path = std::env::temp_dir().join("headache_bench_output_buffer.txt");
+    for (name, output_buffer_size) in [("mandelbrot_unbuffered", 1), ("mandelbrot_buffered", OUTPUT_BUFFER_SIZE)] {
+        let options = CompileOptions { output_buffer_size, ..CompileOptions::default() };
+        group.bench_function(name, |b| b.iter(|| {
+            let mut file = std::fs::File::create(&path).unwrap();
+            compile_with_options(black_box(MANDELBROT), &mut &b""[..], &mut file, options).unwrap().run().unwrap()
+        }));
+    }
+    group.finish();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn jit_option_benches(_: &mut Criterion) {}
+
+criterion_group!(benches, parse_benches, interpreter_benches, jit_benches, jit_option_benches);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+use crate::instruction::Instruction;
+
+/// Enum representing an instruction of the flat form of a Brainfuck program.
+///
+/// In the flat form loops are not nested: their bodies are laid out between a `JumpIfZero`
+/// and a `JumpIfNonZero` that hold the index of each other, so a program can be executed with a
+/// single program counter.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Op {
+    /// Any instruction but a loop.
+    Instruction(Instruction),
+    /// Start of a loop, holding the index of its `JumpIfNonZero`. The loop is skipped by jumping
+    /// past it if the current cell is zero.
+    JumpIfZero(usize),
+    /// End of a loop, holding the index of its `JumpIfZero`. The loop body is run again by jumping
+    /// back to it if the current cell is not zero.
+    JumpIfNonZero(usize),
+}
+
+/// Function to lower a vector of Instructions into their flat form.
+///
+/// The instructions are walked with an explicit stack instead of recursion, so deeply nested
+/// loops cannot overflow the call stack.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of Instructions to be lowered.
+pub(crate) fn lower(instructions: &[Instruction]) -> Vec<Op> {
+    let mut ops = vec![];
+    // Instructions left in every loop being lowered, with the index of its `JumpIfZero`.
+    let mut pending = vec![(instructions.iter(), None)];
+
+    while let Some((remaining, start)) = pending.last_mut() {
+        match remaining.next() {
+            Some(Instruction::Loop(body)) => {
+                let start = ops.len();
+                ops.push(Op::JumpIfZero(0));
+                pending.push((body.iter(), Some(start)));
+            }
+            Some(instruction) => ops.push(Op::Instruction(instruction.clone())),
+            None => {
+                if let Some(start) = *start {
+                    let end = ops.len();
+                    ops.push(Op::JumpIfNonZero(start));
+                    ops[start] = Op::JumpIfZero(end);
+                }
+                pending.pop();
+            }
+        }
+    }
+    ops
+}
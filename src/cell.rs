@@ -0,0 +1,101 @@
+use std::fmt::Debug;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// Trait representing the type of the memory cells used by the `Executor`.
+///
+/// Cells wrap around on overflow and are read and written as little-endian bytes by the `Read`
+/// and `Write` instructions. This trait is sealed, it is only implemented for `u8`, `u16` and
+/// `u32`.
+pub trait WrappingCell: sealed::Sealed + Copy + Eq + Debug + 'static {
+    /// Number of bytes of a cell.
+    const BYTES: usize;
+    /// Cell with value 0.
+    const ZERO: Self;
+    /// Cell with value -1, all its bits set.
+    const NEGATIVE_ONE: Self;
+
+    /// Function to add a delta of an `Add` instruction to the cell, wrapping around.
+    ///
+    /// The delta is an 8-bit two's complement value, so it is sign extended to the cell width.
+    fn add_delta(self, delta: u8) -> Self;
+
+    /// Function to add another cell to the cell, wrapping around.
+    fn add_cell(self, other: Self) -> Self;
+
+    /// Function to multiply the cell by a factor of a `MultiplyInto` instruction, wrapping around.
+    ///
+    /// The factor is an 8-bit two's complement value, so it is sign extended to the cell width.
+    fn mul_delta(self, factor: u8) -> Self;
+
+    /// Function to add a delta of an `Add` instruction to the cell, returning `None` if the
+    /// result does not fit in the cell.
+    ///
+    /// The delta is an 8-bit two's complement value, so a negative delta below 0 also overflows.
+    fn checked_add_delta(self, delta: u8) -> Option<Self>;
+
+    /// Function to add another cell multiplied by a factor of a `MultiplyInto` instruction to the
+    /// cell, returning `None` if the result does not fit in the cell.
+    ///
+    /// The factor is an 8-bit two's complement value, only the final result is checked.
+    fn checked_add_product(self, other: Self, factor: u8) -> Option<Self>;
+
+    /// Function to build a cell from its little-endian bytes.
+    ///
+    /// `bytes` must be `Self::BYTES` long.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+
+    /// Function to write the little-endian bytes of the cell.
+    ///
+    /// `bytes` must be `Self::BYTES` long.
+    fn write_le_slice(self, bytes: &mut [u8]);
+}
+
+macro_rules! impl_wrapping_cell {
+    ($cell:ty, $signed:ty) => {
+        impl WrappingCell for $cell {
+            const BYTES: usize = std::mem::size_of::<$cell>();
+            const ZERO: Self = 0;
+            const NEGATIVE_ONE: Self = <$cell>::MAX;
+
+            fn add_delta(self, delta: u8) -> Self {
+                self.wrapping_add(delta as i8 as $signed as $cell)
+            }
+
+            fn add_cell(self, other: Self) -> Self {
+                self.wrapping_add(other)
+            }
+
+            fn mul_delta(self, factor: u8) -> Self {
+                self.wrapping_mul(factor as i8 as $signed as $cell)
+            }
+
+            fn checked_add_delta(self, delta: u8) -> Option<Self> {
+                self.checked_add_signed(delta as i8 as $signed)
+            }
+
+            fn checked_add_product(self, other: Self, factor: u8) -> Option<Self> {
+                let result = self as i64 + other as i64 * factor as i8 as i64;
+                <$cell>::try_from(result).ok()
+            }
+
+            fn from_le_slice(bytes: &[u8]) -> Self {
+                <$cell>::from_le_bytes(bytes.try_into().unwrap())
+            }
+
+            fn write_le_slice(self, bytes: &mut [u8]) {
+                bytes.copy_from_slice(&self.to_le_bytes())
+            }
+        }
+    };
+}
+
+impl_wrapping_cell!(u8, i8);
+impl_wrapping_cell!(u16, i16);
+impl_wrapping_cell!(u32, i32);
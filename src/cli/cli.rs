@@ -1,13 +1,17 @@
 use std::{fs, io};
+use std::fmt::{Display, Formatter};
+use std::io::{Cursor, Read, stdin};
 use clap::Parser;
-use crate::cli::CLIError::{Cli, IO};
+use headache::error::{Error, ParserError};
+use headache::parser::{split_input, OptLevel};
+use crate::cli::CLIError::{Cli, InputFile, IO};
 use crate::cli::Mode::{Executor, Interpreted};
 
 #[derive(Parser)]
 #[clap(name = "Headache")]
 #[clap(version = "0.1.0", author = "CarlosEduardoL")]
 struct Headache {
-    /// Brainfuck script file
+    /// Brainfuck script file, or - to read the script from the standard input
     file: Option<String>,
     /// Run Headache on real-time interpreter mode
     #[clap(short = 'i', long)]
@@ -15,6 +19,40 @@ struct Headache {
     /// Execute literal script
     #[clap(short = 'e', long)]
     execute: Option<String>,
+    /// Compile the script to native code before running it
+    #[clap(short = 'j', long)]
+    jit: bool,
+    /// Use the given string as the script input
+    #[clap(long, conflicts_with_all = ["input_file", "input_numbers"])]
+    input_string: Option<String>,
+    /// Use the content of the given file as the script input
+    #[clap(long, conflicts_with = "input_numbers")]
+    input_file: Option<String>,
+    /// Use the given space-separated decimal numbers (0 to 255) as the script input bytes
+    #[clap(long)]
+    input_numbers: Option<String>,
+    /// Treat the text after the first unescaped '!' of the script as its input
+    #[clap(long, conflicts_with_all = ["interpreter", "input_string", "input_file", "input_numbers"])]
+    split_input: bool,
+    /// Print the x86-64 assembly generated for the script instead of running it
+    #[clap(long, conflicts_with = "interpreter")]
+    emit_asm: bool,
+    /// Print the optimized instructions of the script instead of running it
+    #[clap(long, conflicts_with_all = ["interpreter", "emit_asm"])]
+    dump_ir: bool,
+    /// Check that the script parses and print its stats instead of running it
+    #[clap(long, conflicts_with_all = ["interpreter", "emit_asm", "dump_ir"])]
+    check: bool,
+    /// Print warnings about likely mistakes in the script before running it
+    #[clap(long)]
+    lint: bool,
+    /// Stop the script after executing the given number of steps
+    #[clap(long, conflicts_with = "jit")]
+    max_steps: Option<u64>,
+    /// Optimization level: 0 runs every command on its own, 1 folds runs of commands, 2 also
+    /// replaces common loops and precomputes the output written before any input or loop
+    #[clap(short = 'O', default_value_t = 2, value_parser = clap::value_parser!(u8).range(0..=2))]
+    opt_level: u8,
 }
 
 /// Enum representing the mode in which the Headache program is running.
@@ -26,30 +64,221 @@ pub enum Mode {
     Interpreted,
 }
 
+/// Enum representing where the Brainfuck program reads its input from.
+#[derive(Debug)]
+pub enum Input {
+    /// The input is read from the standard input.
+    Stdin,
+    /// The input is read from an in-memory string.
+    String(String),
+    /// The input is read from a file.
+    File(String),
+    /// The input is read from in-memory bytes.
+    Bytes(Vec<u8>),
+}
+
+impl Input {
+    /// Function to open the input stream.
+    ///
+    /// # Returns
+    ///
+    /// * A Result containing either the input stream or a CLIError.
+    pub fn open(&self) -> Result<Box<dyn Read>, CLIError> {
+        Ok(match self {
+            Input::Stdin => Box::new(stdin()),
+            Input::String(string) => Box::new(Cursor::new(string.clone().into_bytes())),
+            Input::File(file) => Box::new(fs::File::open(file).map_err(|err| InputFile(file.clone(), err))?),
+            Input::Bytes(bytes) => Box::new(Cursor::new(bytes.clone())),
+        })
+    }
+}
+
+/// Struct representing the configuration selected through command line arguments.
+#[derive(Debug)]
+pub struct Config {
+    /// The mode in which the program should run.
+    pub mode: Mode,
+    /// Whether the script should be compiled to native code before running it.
+    pub jit: bool,
+    /// Where the script reads its input from.
+    pub input: Input,
+    /// Whether the assembly generated for the script should be printed instead of running it.
+    #[cfg(target_arch = "x86_64")]
+    pub emit_asm: bool,
+    /// Whether the optimized instructions of the script should be printed instead of running it.
+    pub dump_ir: bool,
+    /// Whether the script should only be checked, printing its stats instead of running it.
+    pub check: bool,
+    /// Whether warnings about likely mistakes in the script should be printed.
+    pub lint: bool,
+    /// Maximum number of steps a script is allowed to execute.
+    pub max_steps: Option<u64>,
+    /// How much the script is optimized.
+    pub opt_level: OptLevel,
+}
+
 /// Enum representing possible errors that can occur when parsing command line arguments.
 #[derive(Debug)]
 pub enum CLIError {
     /// Error indicating that an IO error occurred.
     IO(io::Error),
+    /// Error indicating that the file given with `--input-file` could not be opened.
+    InputFile(String, io::Error),
     /// Error indicating that a command line argument error occurred.
     Cli(String),
 }
 
-/// Function to determine the mode in which the Headache program should run based on command line arguments.
+impl Display for CLIError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IO(err) => write!(f, "Cannot read the script {err}"),
+            InputFile(path, err) => write!(f, "Cannot read the input file {path}: {err}"),
+            Cli(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<io::Error> for CLIError {
+    fn from(err: io::Error) -> Self {
+        IO(err)
+    }
+}
+
+impl std::error::Error for CLIError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IO(err) | InputFile(_, err) => Some(err),
+            Cli(_) => None,
+        }
+    }
+}
+
+/// Enum representing the errors that stop the Headache program, so its functions can use `?` on
+/// both the errors of the command line and the errors of the script.
+#[derive(Debug)]
+pub enum AppError {
+    /// Error indicating that the command line arguments or the script could not be read.
+    Config(CLIError),
+    /// Error indicating that the script could not be parsed, compiled or run.
+    Script(Error),
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Config(err) => write!(f, "{err}"),
+            AppError::Script(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Config(err) => Some(err),
+            AppError::Script(err) => Some(err),
+        }
+    }
+}
+
+impl From<CLIError> for AppError {
+    fn from(err: CLIError) -> Self {
+        AppError::Config(err)
+    }
+}
+
+impl From<Error> for AppError {
+    fn from(err: Error) -> Self {
+        AppError::Script(err)
+    }
+}
+
+impl From<ParserError> for AppError {
+    fn from(err: ParserError) -> Self {
+        AppError::Script(Error::ParseError(err))
+    }
+}
+
+/// Function to determine the configuration of the Headache program based on command line arguments.
 ///
 /// # Returns
 ///
-/// * A Result containing either a Mode or a CLIError.
-pub fn get_mode() -> Result<Mode, CLIError> {
+/// * A Result containing either a Config or a CLIError.
+pub fn get_config() -> Result<Config, CLIError> {
     let opts: Headache = Headache::parse();
 
-    if let Some(file) = opts.file {
-        Ok(Executor(fs::read_to_string(file).map_err(|e| IO(e))?))
+    let source_from_stdin = opts.file.as_deref() == Some("-");
+    let mut mode = if source_from_stdin {
+        Executor(io::read_to_string(stdin())?)
+    } else if let Some(file) = opts.file {
+        Executor(fs::read_to_string(file)?)
     } else if let Some(source) = opts.execute {
-        Ok(Executor(source))
+        Executor(source)
     } else if opts.interpreter {
-        Ok(Interpreted)
+        Interpreted
     } else {
-        Err(Cli("Error: No file provided and not running in interpreted mode or eval mode".to_string()))
+        return Err(Cli("Error: No file provided and not running in interpreted mode or eval mode".to_string()));
+    };
+
+    if opts.jit && !cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        return Err(Cli("Error: The --jit flag is only supported on x86_64 and aarch64".to_string()));
+    }
+    if opts.emit_asm && !cfg!(target_arch = "x86_64") {
+        return Err(Cli("Error: The --emit-asm flag is only supported on x86_64".to_string()));
     }
-}
\ No newline at end of file
+
+    let mut input = if let Some(string) = opts.input_string {
+        Input::String(string)
+    } else if let Some(file) = opts.input_file {
+        Input::File(file)
+    } else if let Some(numbers) = opts.input_numbers {
+        Input::Bytes(parse_numbers(&numbers)?)
+    } else if source_from_stdin {
+        // The standard input was consumed by the script itself, so the script gets no input.
+        Input::Bytes(vec![])
+    } else {
+        Input::Stdin
+    };
+
+    if let (true, Executor(source)) = (opts.split_input, &mode) {
+        if let Some((program, embedded)) = split_input(source) {
+            input = Input::Bytes(embedded.to_vec());
+            mode = Executor(program.to_string());
+        }
+    }
+
+    let opt_level = match opts.opt_level {
+        0 => OptLevel::None,
+        1 => OptLevel::Fold,
+        _ => OptLevel::Full,
+    };
+
+    Ok(Config {
+        mode,
+        jit: opts.jit,
+        #[cfg(target_arch = "x86_64")]
+        emit_asm: opts.emit_asm,
+        dump_ir: opts.dump_ir,
+        check: opts.check,
+        input,
+        lint: opts.lint,
+        max_steps: opts.max_steps,
+        opt_level,
+    })
+}
+
+/// Function to parse space-separated decimal numbers into bytes.
+///
+/// # Returns
+///
+/// * A Result containing either the bytes or a CLIError if a number is not between 0 and 255.
+fn parse_numbers(numbers: &str) -> Result<Vec<u8>, CLIError> {
+    numbers
+        .split_whitespace()
+        .map(|number| {
+            number
+                .parse()
+                .map_err(|_| Cli(format!("Error: Input number '{number}' is not between 0 and 255")))
+        })
+        .collect()
+}
@@ -1,86 +1,333 @@
-use std::io::{BufRead, BufReader, stdin, Stdin, stdout, Stdout, Write};
+use std::io::{IsTerminal, Read, stdin, stdout, Stdout, Write};
 use std::process::exit;
-use headache::compiler::compile;
-use headache::error::{Error, ParserError};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+use headache::compiler::{compile_with_options, CompileOptions};
+#[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+use headache::parser::OptLevel;
 #[cfg(target_arch="x86_64")]
+use headache::compiler::compile_to_asm;
+use headache::error::{Error, ParserError};
 use headache::executor::Executor;
-use crate::cli::{CLIError, get_mode, Mode};
+use headache::lint::{lint, Warning};
+use headache::parser::{check_brackets, format_instructions, parse_with_options, ParseOptions};
+use headache::stats::ProgramStats;
+use crate::cli::{get_config, AppError, Mode};
 
 mod cli;
 
 /// Main function for the Headache Brainfuck interpreter program.
-fn main() -> Result<(), Error> {
-    // Determine the mode in which the program should run based on command line arguments.
-    let mode = match get_mode() {
-        Ok(mode) => mode,
-        Err(err) => {
-            match err {
-                CLIError::IO(io) => { eprintln!("Cannot read the script {}", io) }
-                CLIError::Cli(err) => { eprintln!("{err}") }
-            }
-            exit(1)
+fn main() {
+    if let Err(err) = run() {
+        match err {
+            AppError::Config(err) => eprintln!("{err}"),
+            AppError::Script(err) => print_error(&err),
         }
-    };
+        exit(1)
+    }
+}
 
-    let mut executor = Executor::default();
+/// Function to run the Headache program as configured by the command line arguments.
+///
+/// # Errors
+///
+/// This function returns the errors that stop the program. The errors the user can fix in the
+/// script, such as parse errors, are printed with their context where they are found instead.
+fn run() -> Result<(), AppError> {
+    // Determine the configuration of the program based on command line arguments.
+    let config = get_config()?;
+    let input = config.input.open()?;
+    // Only the JIT reads the input through a mutable reference.
+    #[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+    let mut input = input;
+
+    let stop_flag = install_interrupt_handler();
+    let parse_options = ParseOptions { opt_level: config.opt_level, ..ParseOptions::default() };
 
     // Execute the program based on the determined mode.
-    match mode {
+    match config.mode {
         Mode::Executor(source) => {
+            if config.lint {
+                print_warnings(&source);
+            }
+            if config.check {
+                match parse_with_options(&source, parse_options) {
+                    Ok(instructions) => print_stats(&ProgramStats::of(&instructions)),
+                    Err(err) => print_parse_error(&err, &source),
+                }
+                return Ok(());
+            }
+            if config.dump_ir {
+                match parse_with_options(&source, parse_options) {
+                    Ok(instructions) => print!("{}", format_instructions(&instructions)),
+                    Err(err) => print_parse_error(&err, &source),
+                }
+                return Ok(());
+            }
             #[cfg(target_arch="x86_64")]
-            {
-                let (mut stdin, mut stdout) = (stdin(), stdout());
-                match compile(&source, &mut stdin, &mut stdout) {
-                    Ok(exe) => exe.run()?,
-                    Err(err) => {
-                        match err {
-                            Error::CompileError(_) =>{ executor.execute(&source)? }
-                            _ => {return Err(err);}
+            if config.emit_asm {
+                print!("{}", compile_to_asm(&source)?);
+                return Ok(());
+            }
+            if config.jit {
+                #[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+                {
+                    let mut stdout = stdout();
+                    let options = CompileOptions {
+                        fold_constant_output: config.opt_level == OptLevel::Full,
+                        opt_level: config.opt_level,
+                        ..CompileOptions::default()
+                    };
+                    let compiled = compile_with_options(&source, &mut input, &mut stdout, options);
+                    match compiled {
+                        Ok(mut exe) => {
+                            exe.set_stop_flag(Some(stop_flag));
+                            if let Err(err) = exe.run() {
+                                // The machine code does not keep track of the failing instruction.
+                                print_error(&err);
+                                exit(1)
+                            }
+                            return Ok(());
                         }
-                    },
+                        Err(Error::CompileError(_)) => {}
+                        Err(Error::ParseError(err)) => print_parse_error(&err, &source),
+                        Err(err) => return Err(err.into()),
+                    }
                 }
             }
             // Parse and execute a Brainfuck script from a file.
-            #[cfg(not(target_arch="x86_64"))]
-            executor.execute(&source)?
+            let mut executor = Executor::new(input, stdout());
+            executor.set_max_steps(config.max_steps);
+            executor.set_parse_options(parse_options);
+            executor.set_stop_flag(Some(stop_flag));
+            match executor.execute(&source) {
+                Err(Error::ParseError(err)) => print_parse_error(&err, &source),
+                Err(err) => {
+                    print_runtime_error(&err, &executor);
+                    exit(1)
+                }
+                Ok(()) => {}
+            }
         }
         Mode::Interpreted => {
-            interpreter(&mut executor)?
+            let mut executor = Executor::new(input, stdout());
+            executor.set_max_steps(config.max_steps);
+            executor.set_parse_options(parse_options);
+            executor.set_flush_per_write(true);
+            executor.set_stop_flag(Some(stop_flag.clone()));
+            interpreter(&mut executor, &stop_flag)?
         }
     }
     Ok(())
 }
 
-fn interpreter(executor: &mut Executor<Stdin, Stdout>) -> Result<(), Error> {
+/// Function to make Ctrl-C set the returned flag instead of killing the process.
+///
+/// The running program checks the flag and stops with an `Interrupted` error, so the REPL
+/// survives it and buffered output is not lost. A second Ctrl-C before the flag is cleared
+/// exits, as a compiled program that neither reads nor writes never checks the flag.
+fn install_interrupt_handler() -> Arc<AtomicBool> {
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let handler_flag = stop_flag.clone();
+    let installed = ctrlc::set_handler(move || {
+        if handler_flag.swap(true, Ordering::Relaxed) {
+            exit(130)
+        }
+    });
+    if let Err(err) = installed {
+        eprintln!("Warning: Ctrl-C cannot be handled, it will kill the process: {err}");
+    }
+    stop_flag
+}
+
+/// Function to print the linter warnings of a script to stderr.
+///
+/// Scripts that cannot be parsed are skipped, the parse error is reported when running them.
+fn print_warnings(source: &str) {
+    for warning in lint(source).unwrap_or_default() {
+        match warning {
+            Warning::DeadWrite { position } => {
+                eprintln!("Warning: the value written at position {position} is never read")
+            }
+        }
+    }
+}
+
+/// Function to print the stats of a script to stdout, one per line.
+fn print_stats(stats: &ProgramStats) {
+    println!("Instructions: {}", stats.instructions);
+    println!("Max loop depth: {}", stats.max_loop_depth);
+    println!("Reads: {}", stats.reads);
+    println!("Writes: {}", stats.writes);
+    match stats.tape_extent {
+        Some((low, high)) => println!("Tape extent: {low:+} to {high:+}"),
+        None => println!("Tape extent: unknown"),
+    }
+}
+
+/// ANSI style of the error labels and carets.
+const ERROR_STYLE: &str = "1;31";
+/// ANSI style of the line numbers in front of the source.
+const GUTTER_STYLE: &str = "1;34";
+
+/// Function to tell whether the messages written to stderr can be colored.
+///
+/// Colors are disabled when stderr is not a terminal or the `NO_COLOR` environment variable is
+/// set to a non-empty value, see <https://no-color.org>.
+fn use_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty()) && std::io::stderr().is_terminal()
+}
+
+/// Function to wrap text in an ANSI style if the messages written to stderr can be colored.
+fn paint(text: &str, style: &str) -> String {
+    if use_color() {
+        format!("\x1b[{style}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Function to print an error message to stderr.
+fn print_error(err: &dyn std::fmt::Display) {
+    eprintln!("{}: {err}", paint("Error", ERROR_STYLE));
+}
+
+/// Function to print a parse error to stderr, showing the line of the source where it is with
+/// a caret under the offending bracket.
+fn report_parse_error(err: &ParserError, source: &str) {
+    print_error(err);
+    let (line, _) = err.line_col();
+    let caret = err.caret(source);
+    let (text, caret) = caret.split_once('\n').unwrap_or_default();
+    let label = match err {
+        ParserError::IncompleteLoop { .. } => "this '[' is never closed",
+        ParserError::UnexpectedToken { .. } => "this ']' has no matching '['",
+    };
+    let gutter = " ".repeat(line.to_string().len());
+    eprintln!("{} {text}", paint(&format!("{line} |"), GUTTER_STYLE));
+    eprintln!("{} {}", paint(&format!("{gutter} |"), GUTTER_STYLE), paint(&format!("{caret} {label}"), ERROR_STYLE));
+}
+
+/// Function to print a parse error to stderr, showing where it is in the script, and exit.
+fn print_parse_error(err: &ParserError, source: &str) -> ! {
+    report_parse_error(err, source);
+    exit(1)
+}
+
+/// Function to print an error that stopped a running script to stderr, naming the instruction
+/// that failed and where the data pointer was.
+fn print_runtime_error<Input: Read, Output: Write>(err: &Error, executor: &Executor<Input, Output>) {
+    print_error(err);
+    let pointer = executor.pointer();
+    let cell = executor.memory[pointer].0;
+    match executor.failed_instruction() {
+        Some(instruction) => {
+            let instruction = format_instructions(std::slice::from_ref(instruction));
+            eprintln!("  while running {} with the data pointer at cell {pointer} holding {cell}", instruction.trim_end());
+        }
+        None => eprintln!("  with the data pointer at cell {pointer} holding {cell}"),
+    }
+}
+
+/// Help text of the commands understood by the interpreter besides Brainfuck code.
+const REPL_HELP: &str = "\
+:reset  Zero the memory and move the data pointer back to the first cell
+:dump   Print the data pointer and the non-zero memory cells
+:begin  Start a block of lines run as a single program once :end is written
+:help   Print this help
+exit    Finish the interpreter";
+
+fn interpreter(executor: &mut Executor<Box<dyn Read>, Stdout>, stop_flag: &AtomicBool) -> Result<(), Error> {
     // Run the program in real-time interpreter mode.
     let mut buffer = String::new();
-    println!("Write exit to finish the interpreter");
+    // Whether the lines are being collected between `:begin` and `:end`.
+    let mut in_block = false;
+    println!("Write exit to finish the interpreter, or :help to list the commands");
     loop {
-        if buffer.is_empty() {
+        if buffer.is_empty() && !in_block {
             print!(">")
         } else {
             print!("==>")
         }
         stdout().flush().unwrap();
-        let mut reader = BufReader::new(stdin());
-        reader.read_line(&mut buffer).unwrap();
-        if buffer.contains("exit") {
-            exit(0)
+        let mut line = String::new();
+        if stdin().read_line(&mut line)? == 0 {
+            // The standard input is over, there is nothing left to run.
+            return Ok(());
         }
-        match executor.execute(&buffer) {
-            Ok(_) => {},
-            Err(err) => match err{
-                Error::ParseError(err) => match err {
-                    ParserError::IncompleteLoop => {continue;}
-                    ParserError::UnexpectedToken => {
-                        eprintln!("Error: Cannot close ']' without first open '[' it")
+        if in_block {
+            if line.trim() != ":end" {
+                buffer.push_str(&line);
+                continue;
+            }
+            in_block = false;
+            // The block is over, so a loop left open is an error instead of waiting for more lines.
+            if let Err(err) = check_brackets(&buffer) {
+                report_parse_error(&err, &buffer);
+                buffer.clear();
+                continue;
+            }
+            run_buffer(executor, &mut buffer, stop_flag)?;
+            continue;
+        }
+        // Commands are only recognized as whole lines outside of an unfinished loop.
+        if buffer.is_empty() {
+            match line.trim() {
+                "exit" => exit(0),
+                ":reset" => {
+                    executor.reset();
+                    continue;
+                }
+                ":dump" => {
+                    println!("pointer: {}", executor.pointer());
+                    for (index, value) in executor.dump_nonzero() {
+                        println!("[{index}] = {value}");
                     }
+                    continue;
+                }
+                ":help" => {
+                    println!("{REPL_HELP}");
+                    continue;
                 }
-                Error::RuntimeError(_) => {return Err(err)},
-                #[cfg(target_arch="x86_64")]
-                Error::CompileError(_) => {return Err(err);}
+                ":begin" => {
+                    in_block = true;
+                    continue;
+                }
+                _ => {}
             }
         }
-        buffer.clear()
+        buffer.push_str(&line);
+        // A loop left open waits for the lines that close it, an extra ']' is a typo.
+        match check_brackets(&buffer) {
+            Ok(()) => {}
+            Err(ParserError::IncompleteLoop { .. }) => continue,
+            Err(err) => {
+                report_parse_error(&err, &buffer);
+                buffer.clear();
+                continue;
+            }
+        }
+        run_buffer(executor, &mut buffer, stop_flag)?;
     }
-}
\ No newline at end of file
+}
+
+/// Function to run the code collected by the REPL and clear it, reporting the errors the REPL
+/// survives.
+///
+/// # Errors
+///
+/// This function returns the errors of the run that end the REPL, such as a failed read or write.
+fn run_buffer(executor: &mut Executor<Box<dyn Read>, Stdout>, buffer: &mut String, stop_flag: &AtomicBool) -> Result<(), Error> {
+    stop_flag.store(false, Ordering::Relaxed);
+    match executor.execute(buffer) {
+        Ok(_) => {},
+        Err(err) => match err{
+            Error::ParseError(err) => report_parse_error(&err, buffer),
+            Error::StepLimitExceeded | Error::Interrupted => print_runtime_error(&err, executor),
+            _ => {return Err(err)},
+        }
+    }
+    buffer.clear();
+    Ok(())
+}
@@ -0,0 +1,414 @@
+use dynasmrt::{aarch64::Aarch64Relocation, dynasm, Assembler, DynamicLabel, DynasmApi, DynasmLabelApi};
+
+use super::{CellWidth, IoContext, OutOfBounds, WrapMode};
+use crate::instruction::Instruction;
+use crate::MEMORY_SIZE;
+use std::io::{Read, Write};
+
+/// Type of the relocations of the ARM64 machine code.
+pub(super) type Relocation = Aarch64Relocation;
+
+/// Type of the function generated by `compile_program`.
+pub(super) type Entry = unsafe extern "C" fn(*mut u8, *mut ()) -> *mut std::io::Error;
+
+/// Function to generate the machine code of a Brainfuck program run in-process by `Executable`.
+///
+/// The generated function follows the AAPCS64 calling convention: it takes the pointer to the
+/// memory array in `x0` and the pointer to the `IoContext` in `x1`, and returns a null pointer or
+/// a pointer to an `std::io::Error` in `x0`. The memory array pointer is kept in `x19`, the data
+/// pointer offset in `x20` and the I/O context pointer in `x21`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be compiled.
+/// * `code` - An `Assembler` object from the `dynasmrt` crate to which the generated machine code
+///   will be added.
+/// * `wrap` - How the generated machine code moves the data pointer, see `WrapMode`.
+/// * `cell_width` - The width of the memory cells, only 8-bit cells are compiled on ARM64 and
+///   `compile_with_options` rejects the others.
+pub(super) fn compile_program(
+    instructions: &[Instruction],
+    code: &mut Assembler<Aarch64Relocation>,
+    wrap: WrapMode,
+    cell_width: CellWidth,
+) {
+    debug_assert_eq!(cell_width, CellWidth::U8);
+    dynasm! { code
+        ; .arch aarch64
+        ; stp x29, x30, [sp, #-48]!
+        ; mov x29, sp
+        ; stp x19, x20, [sp, #16]
+        ; str x21, [sp, #32]
+        ; mov x19, x0 // pointer to memory
+        ; mov x20, xzr // offset from x19
+        ; mov x21, x1 // pointer to the I/O context
+    };
+
+    compile_segment(instructions, code, wrap);
+
+    dynasm! { code
+        ; .arch aarch64
+        ; mov x0, xzr // clear return register if not early return
+        ; ->exit:
+        ; ldr x21, [sp, #32]
+        ; ldp x19, x20, [sp, #16]
+        ; ldp x29, x30, [sp], #48
+        ; ret
+    }
+
+    if checks_bounds(wrap) {
+        dynasm! { code
+            ; .arch aarch64
+            ; ->out_of_bounds:
+            ; mov     x0, x9
+            ; ldr     x2, [x21, #16]
+            ; blr     x2
+            ; b       ->exit
+        }
+    }
+}
+
+/// Function to generate machine code for a segment of Brainfuck instructions.
+///
+/// This function takes a slice of `Instruction` values and generates machine code for each instruction
+/// using the `dynasm!` macro from the `dynasmrt` crate. It is a helper function used by
+/// `compile_program`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be compiled.
+/// * `code` - An `Assembler` object from the `dynasmrt` crate to which the generated machine code
+///   will be added.
+/// * `wrap` - How the generated machine code moves the data pointer, see `WrapMode`.
+fn compile_segment(instructions: &[Instruction], code: &mut Assembler<Aarch64Relocation>, wrap: WrapMode) {
+    for instruction in instructions {
+        match instruction {
+            Instruction::Move(n) => {
+                compile_offset_index(*n, code, wrap);
+                dynasm! { code
+                    ; .arch aarch64
+                    ; mov     x20, x9
+                }
+            }
+            Instruction::Add(n) => {
+                dynasm! { code
+                    ; .arch aarch64
+                    ; ldrb    w9, [x19, x20]
+                    ; add     w9, w9, *n as u32
+                    ; strb    w9, [x19, x20]
+                }
+            }
+            Instruction::AddAt { offset, value } => {
+                compile_offset_index(*offset, code, wrap);
+                dynasm! { code
+                    ; .arch aarch64
+                    ; ldrb    w13, [x19, x9]
+                    ; add     w13, w13, *value as u32
+                    ; strb    w13, [x19, x9]
+                }
+            }
+            Instruction::Write => {
+                dynasm! { code
+                    ; .arch aarch64
+                    ; add     x0, x19, x20
+                    ; mov     x1, x21
+                    ; ldr     x2, [x21, #8]
+                    ; blr     x2
+                    ; cbnz    x0, ->exit
+                }
+            }
+            Instruction::Read => {
+                dynasm! { code
+                    ; .arch aarch64
+                    ; add     x0, x19, x20
+                    ; mov     x1, x21
+                    ; ldr     x2, [x21]
+                    ; blr     x2
+                    ; cbnz    x0, ->exit
+                }
+            }
+            Instruction::Loop(loop_segment) => {
+                let loop_label = code.new_dynamic_label();
+                let end_label = code.new_dynamic_label();
+                dynasm! { code
+                    ; .arch aarch64
+                    ; =>loop_label
+                    ; ldrb    w9, [x19, x20]
+                    ; cbz     w9, =>end_label
+                }
+                compile_segment(loop_segment, code, wrap);
+                dynasm! { code
+                    ; .arch aarch64
+                    ; ldrb    w9, [x19, x20]
+                    ; cbnz    w9, =>loop_label
+                    ; =>end_label
+                }
+            }
+            Instruction::Clear => {
+                dynasm! { code
+                    ; .arch aarch64
+                    ; strb    wzr, [x19, x20]
+                }
+            }
+            Instruction::Set(n) => {
+                dynasm! { code
+                    ; .arch aarch64
+                    ; movz    w9, *n as u32
+                    ; strb    w9, [x19, x20]
+                }
+            }
+            Instruction::AddTo { offset } => {
+                let skip_label = compile_skip_if_zero(code, wrap);
+                compile_offset_index(*offset, code, wrap);
+                dynasm! { code
+                    ; .arch aarch64
+                    ; ldrb    w12, [x19, x20]
+                    ; ldrb    w13, [x19, x9]
+                    ; add     w13, w13, w12
+                    ; strb    w13, [x19, x9]
+                    ; strb    wzr, [x19, x20]
+                }
+                compile_skip_label(skip_label, code);
+            }
+            Instruction::ClearScan { stride } => {
+                compile_segment(&[Instruction::Loop(vec![Instruction::Clear, Instruction::Move(*stride)])], code, wrap)
+            }
+            Instruction::Seek { stride } => {
+                let loop_label = code.new_dynamic_label();
+                let end_label = code.new_dynamic_label();
+                dynasm! { code
+                    ; .arch aarch64
+                    ; =>loop_label
+                    ; ldrb    w9, [x19, x20]
+                    ; cbz     w9, =>end_label
+                }
+                compile_offset_index(*stride, code, wrap);
+                dynasm! { code
+                    ; .arch aarch64
+                    ; mov     x20, x9
+                    ; b       =>loop_label
+                    ; =>end_label
+                }
+            }
+            Instruction::MultiplyInto { targets } => {
+                let skip_label = compile_skip_if_zero(code, wrap);
+                dynasm! { code
+                    ; .arch aarch64
+                    ; ldrb    w12, [x19, x20]
+                }
+                for &(offset, factor) in targets {
+                    compile_offset_index(offset, code, wrap);
+                    dynasm! { code
+                        ; .arch aarch64
+                        ; ldrb    w13, [x19, x9]
+                        ; movz    w14, factor as u32
+                        ; madd    w13, w12, w14, w13
+                        ; strb    w13, [x19, x9]
+                    }
+                }
+                dynasm! { code
+                    ; .arch aarch64
+                    ; strb    wzr, [x19, x20]
+                }
+                compile_skip_label(skip_label, code);
+            }
+            // Compiled programs do not dump the memory, only the `Executor` does.
+            Instruction::DebugDump => {}
+            Instruction::WriteConst(constant) => {
+                // The string is embedded in the machine code, jumped over.
+                dynasm! { code
+                    ; .arch aarch64
+                    ; b       >after
+                    ; constant:
+                    ; .bytes  constant.iter()
+                    ; .align  4
+                    ; after:
+                    ; adr     x0, <constant
+                    ; movz    x1, (constant.len() & 0xffff) as u32
+                    ; movk    x1, (constant.len() >> 16 & 0xffff) as u32, lsl 16
+                    ; mov     x2, x21
+                    ; ldr     x3, [x21, #24]
+                    ; blr     x3
+                    ; cbnz    x0, ->exit
+                }
+            }
+        }
+    }
+}
+
+/// Function to generate machine code skipping an `AddTo` or `MultiplyInto` instruction when the
+/// current cell is zero, which is only needed for `WrapMode::Assume`.
+///
+/// The loop replaced by those instructions does not run when the current cell is zero, so a
+/// program may rely on it not touching its targets then, even if they are out of the memory array.
+///
+/// # Returns
+///
+/// * The label to be placed after the instruction with `compile_skip_label`, or None if nothing
+///   was generated.
+fn compile_skip_if_zero(code: &mut Assembler<Aarch64Relocation>, wrap: WrapMode) -> Option<DynamicLabel> {
+    if wrap != WrapMode::Assume {
+        return None;
+    }
+    let skip_label = code.new_dynamic_label();
+    dynasm! { code
+        ; .arch aarch64
+        ; ldrb    w9, [x19, x20]
+        ; cbz     w9, =>skip_label
+    }
+    Some(skip_label)
+}
+
+/// Function to place the label returned by `compile_skip_if_zero`, if any.
+fn compile_skip_label(skip_label: Option<DynamicLabel>, code: &mut Assembler<Aarch64Relocation>) {
+    if let Some(skip_label) = skip_label {
+        dynasm! { code
+            ; .arch aarch64
+            ; =>skip_label
+        }
+    }
+}
+
+/// Function to generate machine code computing the index `offset` cells away from the data pointer.
+///
+/// The index is left in `x9`. With `WrapMode::Wrap` it wraps around the memory array boundaries
+/// and the generated code overwrites `x10` and `x11`. With `WrapMode::Assume` it is a plain sum,
+/// checked against the memory array boundaries in debug builds.
+///
+/// # Arguments
+///
+/// * `offset` - The distance from the data pointer, it can be negative.
+/// * `code` - An `Assembler` object from the `dynasmrt` crate to which the generated machine code
+///   will be added.
+/// * `wrap` - How the index is computed.
+fn compile_offset_index(offset: isize, code: &mut Assembler<Aarch64Relocation>, wrap: WrapMode) {
+    if wrap == WrapMode::Assume {
+        let offset = offset as i64;
+        if (0..4096).contains(&offset) {
+            dynasm! { code
+                ; .arch aarch64
+                ; add     x9, x20, offset as u32
+            }
+        } else if (-4095..0).contains(&offset) {
+            dynasm! { code
+                ; .arch aarch64
+                ; sub     x9, x20, -offset as u32
+            }
+        } else {
+            dynasm! { code
+                ; .arch aarch64
+                ; movz    x9, (offset & 0xffff) as u32
+                ; movk    x9, (offset >> 16 & 0xffff) as u32, lsl 16
+                ; movk    x9, (offset >> 32 & 0xffff) as u32, lsl 32
+                ; movk    x9, (offset >> 48 & 0xffff) as u32, lsl 48
+                ; add     x9, x20, x9
+            }
+        }
+        if checks_bounds(wrap) {
+            dynasm! { code
+                ; .arch aarch64
+                ; movz    x10, MEMORY_SIZE as u32
+                ; cmp     x9, x10
+                ; b.hs    ->out_of_bounds
+            }
+        }
+        return;
+    }
+    // The offset is reduced at compile time, so a single subtraction wraps the sum around.
+    let offset = offset.rem_euclid(MEMORY_SIZE as isize) as u32;
+    dynasm! { code
+        ; .arch aarch64
+        ; movz    x9, offset
+        ; add     x9, x20, x9
+        ; movz    x10, MEMORY_SIZE as u32
+        ; subs    x11, x9, x10
+        ; csel    x9, x11, x9, hs
+    }
+}
+
+/// Function to get the addresses of the functions called by the machine code to read and write
+/// a byte and to write a constant string, to be stored in the `IoContext`.
+pub(super) fn io_functions<Input: Read, Output: Write>(cell_width: CellWidth) -> (*const (), *const (), *const ()) {
+    debug_assert_eq!(cell_width, CellWidth::U8);
+    (
+        read::<Input, Output> as *const (),
+        write::<Input, Output> as *const (),
+        write_const::<Input, Output> as *const (),
+    )
+}
+
+/// Function to read a single byte from the input stream of an I/O context.
+///
+/// This function is an extern "C" function that is called by the generated machine code to read a single byte from the input stream and store it in the given memory location. It returns a null pointer if the read is successful or a pointer to an `std::io::Error` object if an error occurs.
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the memory location where the read byte should be stored.
+/// * `io` - A pointer to the I/O context holding the input stream from which to read the byte.
+extern "C" fn read<Input: Read, Output: Write>(
+    ptr: &mut [u8; 1],
+    io: &mut IoContext<Input, Output>,
+) -> *mut std::io::Error {
+    match io.read_cell(ptr) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(err) => Box::into_raw(Box::new(err)),
+    }
+}
+
+/// Function to write a single byte to the output stream of an I/O context.
+///
+/// This function is an extern "C" function that is called by the generated machine code to write a single byte from the given memory location to the output stream. It returns a null pointer if the write is successful or a pointer to an `std::io::Error` object if an error occurs.
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the memory location containing the byte to be written.
+/// * `io` - A pointer to the I/O context holding the output stream to which the byte should be written.
+extern "C" fn write<Input: Read, Output: Write>(
+    ptr: &mut [u8; 1],
+    io: &mut IoContext<Input, Output>,
+) -> *mut std::io::Error {
+    match io.write_bytes(ptr) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(err) => Box::into_raw(Box::new(err)),
+    }
+}
+
+/// Function to write a constant string of bytes to the output stream of an I/O context.
+///
+/// This function is an extern "C" function that is called by the generated machine code for a `WriteConst` instruction. It returns a null pointer if the write is successful or a pointer to an `std::io::Error` object if an error occurs.
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the first byte of the string, embedded in the machine code.
+/// * `len` - The number of bytes of the string.
+/// * `io` - A pointer to the I/O context holding the output stream to which the bytes should be written.
+extern "C" fn write_const<Input: Read, Output: Write>(
+    ptr: *const u8,
+    len: usize,
+    io: &mut IoContext<Input, Output>,
+) -> *mut std::io::Error {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+    match io.write_bytes(bytes) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(err) => Box::into_raw(Box::new(err)),
+    }
+}
+
+/// Function to tell whether the generated machine code checks the data pointer against the
+/// memory array boundaries, which is only done for `WrapMode::Assume` in debug builds.
+fn checks_bounds(wrap: WrapMode) -> bool {
+    wrap == WrapMode::Assume && cfg!(debug_assertions)
+}
+
+/// Function to report that the data pointer moved out of the memory array.
+///
+/// This function is an extern "C" function that is called by the generated machine code when a
+/// bounds check fails, see `WrapMode::Assume`. It returns a pointer to an `std::io::Error`
+/// carrying the index, turned into a `PointerOutOfBounds` error by `Executable`.
+///
+/// # Arguments
+///
+/// * `index` - The index of the data pointer, a negative index wraps to a huge one.
+pub(super) extern "C" fn out_of_bounds(index: usize) -> *mut std::io::Error {
+    Box::into_raw(Box::new(std::io::Error::other(OutOfBounds(index as isize))))
+}
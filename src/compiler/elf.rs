@@ -0,0 +1,107 @@
+/// Size of the ELF file header.
+const HEADER_SIZE: usize = 64;
+/// Size of an ELF section header.
+const SECTION_HEADER_SIZE: usize = 64;
+/// Size of an ELF symbol table entry.
+const SYMBOL_SIZE: usize = 24;
+
+/// Names of the sections, in the order of their headers after the null one.
+const SECTION_NAMES: [&str; 5] = [".text", ".symtab", ".strtab", ".shstrtab", ".note.GNU-stack"];
+
+/// Function to build a 64-bit little-endian x86-64 relocatable ELF object file.
+///
+/// The object file contains a single `.text` section holding `text`, with a global function
+/// symbol named `symbol` at its start. The machine code cannot reference anything outside of
+/// itself, as the object file has no relocations. An empty `.note.GNU-stack` section marks the
+/// stack as non-executable for the linker.
+///
+/// # Arguments
+///
+/// * `text` - The machine code of the function.
+/// * `symbol` - The name of the function.
+pub(crate) fn relocatable_object(text: &[u8], symbol: &str) -> Vec<u8> {
+    let mut shstrtab = vec![0u8];
+    let mut name_offsets = vec![];
+    for name in SECTION_NAMES {
+        name_offsets.push(shstrtab.len() as u32);
+        shstrtab.extend_from_slice(name.as_bytes());
+        shstrtab.push(0);
+    }
+
+    let mut strtab = vec![0u8];
+    strtab.extend_from_slice(symbol.as_bytes());
+    strtab.push(0);
+
+    let mut symtab = vec![0u8; SYMBOL_SIZE];
+    symtab.extend_from_slice(&1u32.to_le_bytes()); // name, right after the leading 0 of strtab
+    symtab.push(0x12); // global function
+    symtab.push(0); // default visibility
+    symtab.extend_from_slice(&1u16.to_le_bytes()); // defined in .text
+    symtab.extend_from_slice(&0u64.to_le_bytes()); // value
+    symtab.extend_from_slice(&(text.len() as u64).to_le_bytes()); // size
+
+    let mut object = vec![0u8; HEADER_SIZE];
+    let text_offset = append_aligned(&mut object, text, 16);
+    let symtab_offset = append_aligned(&mut object, &symtab, 8);
+    let strtab_offset = append_aligned(&mut object, &strtab, 1);
+    let shstrtab_offset = append_aligned(&mut object, &shstrtab, 1);
+    let section_headers_offset = append_aligned(&mut object, &[], 8);
+
+    write_header(&mut object, section_headers_offset);
+    object.extend_from_slice(&[0u8; SECTION_HEADER_SIZE]);
+    let sections = [
+        // name, type, flags, offset, size, link, info, alignment, entry size
+        (name_offsets[0], 1, 0x6, text_offset, text.len(), 0, 0, 16, 0),
+        (name_offsets[1], 2, 0, symtab_offset, symtab.len(), 3, 1, 8, SYMBOL_SIZE),
+        (name_offsets[2], 3, 0, strtab_offset, strtab.len(), 0, 0, 1, 0),
+        (name_offsets[3], 3, 0, shstrtab_offset, shstrtab.len(), 0, 0, 1, 0),
+        (name_offsets[4], 1, 0, shstrtab_offset, 0, 0, 0, 1, 0),
+    ];
+    for (name, kind, flags, offset, size, link, info, alignment, entry_size) in sections {
+        object.extend_from_slice(&name.to_le_bytes());
+        object.extend_from_slice(&(kind as u32).to_le_bytes());
+        object.extend_from_slice(&(flags as u64).to_le_bytes());
+        object.extend_from_slice(&0u64.to_le_bytes()); // address
+        object.extend_from_slice(&(offset as u64).to_le_bytes());
+        object.extend_from_slice(&(size as u64).to_le_bytes());
+        object.extend_from_slice(&(link as u32).to_le_bytes());
+        object.extend_from_slice(&(info as u32).to_le_bytes());
+        object.extend_from_slice(&(alignment as u64).to_le_bytes());
+        object.extend_from_slice(&(entry_size as u64).to_le_bytes());
+    }
+    object
+}
+
+/// Function to append `bytes` to the object file at an offset multiple of `alignment`.
+///
+/// # Returns
+///
+/// * The offset at which the bytes were appended.
+fn append_aligned(object: &mut Vec<u8>, bytes: &[u8], alignment: usize) -> usize {
+    object.resize(object.len().next_multiple_of(alignment), 0);
+    let offset = object.len();
+    object.extend_from_slice(bytes);
+    offset
+}
+
+/// Function to write the ELF file header at the start of the object file.
+fn write_header(object: &mut [u8], section_headers_offset: usize) {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(b"\x7fELF");
+    header.extend_from_slice(&[2, 1, 1, 0]); // 64-bit, little-endian, version 1, System V ABI
+    header.extend_from_slice(&[0; 8]);
+    header.extend_from_slice(&1u16.to_le_bytes()); // relocatable
+    header.extend_from_slice(&62u16.to_le_bytes()); // x86-64
+    header.extend_from_slice(&1u32.to_le_bytes()); // version
+    header.extend_from_slice(&0u64.to_le_bytes()); // entry point
+    header.extend_from_slice(&0u64.to_le_bytes()); // program headers offset
+    header.extend_from_slice(&(section_headers_offset as u64).to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes()); // flags
+    header.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes());
+    header.extend_from_slice(&0u16.to_le_bytes()); // program header size
+    header.extend_from_slice(&0u16.to_le_bytes()); // program headers count
+    header.extend_from_slice(&(SECTION_HEADER_SIZE as u16).to_le_bytes());
+    header.extend_from_slice(&(SECTION_NAMES.len() as u16 + 1).to_le_bytes());
+    header.extend_from_slice(&4u16.to_le_bytes()); // index of .shstrtab
+    object[..HEADER_SIZE].copy_from_slice(&header);
+}
@@ -0,0 +1,739 @@
+//! Compiler of Brainfuck programs to native code.
+//!
+//! Programs are parsed by a common front-end, then the `Instruction` values are turned into machine
+//! code by the backend of the target architecture: `x64` on x86-64 and `aarch64` on ARM64.
+
+#[cfg(target_arch="aarch64")]
+mod aarch64;
+#[cfg(target_arch="x86_64")]
+mod elf;
+#[cfg(target_arch="x86_64")]
+mod x64;
+
+#[cfg(target_arch="aarch64")]
+use aarch64 as backend;
+#[cfg(target_arch="x86_64")]
+use x64 as backend;
+
+use crate::MEMORY_SIZE;
+use crate::error::Error;
+use crate::instruction::drop_instructions;
+use crate::parser::{fold_constant_output, parse_with_options, OptLevel, ParseOptions};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_arch="x86_64")]
+use crate::parser::parse;
+#[cfg(target_arch="x86_64")]
+use std::path::Path;
+
+/// Default size in bytes of the buffer where compiled programs accumulate their output.
+pub const OUTPUT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Enum representing how compiled programs move the data pointer.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum WrapMode {
+    /// The data pointer wraps around the ends of the memory array, like the `Executor` does.
+    #[default]
+    Wrap,
+    /// The program is assumed to never move the data pointer out of the memory array, so moves
+    /// are plain additions. This is much faster, but a program that does move out of the memory
+    /// array reads and writes memory it does not own. Debug builds check every move and stop
+    /// the program with a `PointerOutOfBounds` error instead.
+    Assume,
+}
+
+/// Enum representing the width of the memory cells of compiled programs.
+///
+/// Wider cells behave like the cells of an `Executor` of the same width: the deltas of `Add`
+/// instructions are sign extended, and `Read` and `Write` move as many bytes as the cell holds,
+/// in little-endian order. The memory array of a program with wider cells takes `MEMORY_SIZE`
+/// times the width in bytes. Only x86-64 compiles cells wider than a byte, on ARM64 they are a
+/// `CompileError`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum CellWidth {
+    /// 8-bit cells, like `u8` cells.
+    #[default]
+    U8,
+    /// 16-bit cells, like `u16` cells.
+    U16,
+    /// 32-bit cells, like `u32` cells.
+    U32,
+}
+
+impl CellWidth {
+    /// Function to get the number of bytes of a cell.
+    pub fn bytes(self) -> usize {
+        match self {
+            CellWidth::U8 => 1,
+            CellWidth::U16 => 2,
+            CellWidth::U32 => 4,
+        }
+    }
+}
+
+/// Struct representing the options of `compile_with_options`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CompileOptions {
+    /// The size in bytes of the output buffer, see `compile_with_output_buffer`.
+    pub output_buffer_size: usize,
+    /// How the data pointer is moved.
+    pub wrap_mode: WrapMode,
+    /// Whether the output produced before the program depends on its input is computed at
+    /// compile time, see `parser::fold_constant_output`. The program must then be run on a
+    /// zeroed memory array. The output is only folded for `CellWidth::U8`, as the evaluation
+    /// wraps the cells at a byte.
+    pub fold_constant_output: bool,
+    /// How much the parser optimizes the program, see `parser::OptLevel`.
+    pub opt_level: OptLevel,
+    /// The width of the memory cells, see `CellWidth`.
+    pub cell_width: CellWidth,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            output_buffer_size: OUTPUT_BUFFER_SIZE,
+            wrap_mode: WrapMode::Wrap,
+            fold_constant_output: false,
+            opt_level: OptLevel::Full,
+            cell_width: CellWidth::U8,
+        }
+    }
+}
+
+/// Struct representing a compiled Brainfuck program.
+///
+/// The `Executable` struct contains an `ExecutableBuffer` object from the `dynasmrt` crate,
+/// which holds the generated machine code for the Brainfuck program, and the I/O context
+/// through which the machine code reads and writes data.
+pub struct Executable<'a> {
+    code: dynasmrt::ExecutableBuffer,
+    io: Box<dyn BufferedOutput + 'a>,
+    cell_width: CellWidth,
+}
+
+impl<'a> Executable<'a> {
+    /// Function to create a new `Executable` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - An `ExecutableBuffer` object from the `dynasmrt` crate containing the generated
+    ///   machine code for the Brainfuck program.
+    /// * `io` - The I/O context to be passed to the machine code.
+    /// * `cell_width` - The width of the memory cells the machine code was generated for.
+    fn new(code: dynasmrt::ExecutableBuffer, io: Box<dyn BufferedOutput + 'a>, cell_width: CellWidth) -> Self {
+        Executable { code, io, cell_width }
+    }
+
+    /// Function to get the generated machine code, to cache it or to inspect it with a disassembler.
+    ///
+    /// The code is a single function that starts at the first byte. It does not depend on the
+    /// address it is loaded at, so it can be copied to any executable memory and called there.
+    ///
+    /// # ABI
+    ///
+    /// On x86-64 the function follows the sysv64 calling convention, and on ARM64 the AAPCS64 one:
+    ///
+    /// ```text
+    /// fn(memory: *mut u8, context: *mut Context) -> *mut std::io::Error
+    /// ```
+    ///
+    /// * `memory` - A pointer to a memory array of `MEMORY_SIZE` cells of the `CellWidth` the
+    ///   program was compiled for.
+    /// * `context` - A pointer to a structure starting with the addresses of four functions,
+    ///   in this order, using the same calling convention and returning a null pointer on
+    ///   success or a pointer to a boxed `std::io::Error`:
+    ///   * `read(cell: *mut u8, context: *mut Context)`, storing the bytes of input of a cell in it.
+    ///   * `write(cell: *const u8, context: *mut Context)`, writing the bytes of the cell.
+    ///   * `out_of_bounds(index: usize)`, only called by code compiled with `WrapMode::Assume`
+    ///     in a debug build when the data pointer leaves the memory array.
+    ///   * `write_const(bytes: *const u8, len: usize, context: *mut Context)`, writing a string
+    ///     of bytes embedded in the code.
+    ///
+    /// The function returns a null pointer when the program finishes, or the first non-null
+    /// pointer returned by one of the functions, stopping the program.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::compiler::compile;
+    ///
+    /// let (mut input, mut output) = (Cursor::new(b""), Vec::new());
+    /// let executable = compile("+.", &mut input, &mut output).unwrap();
+    ///
+    /// assert!(!executable.machine_code().is_empty());
+    /// ```
+    pub fn machine_code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// Function to set a flag that stops the compiled program when it is set.
+    ///
+    /// The machine code does not poll the flag, it is checked when the program reads or writes
+    /// a byte. The buffered output, including the byte being written, is written to the output
+    /// stream before the program stops with an `Interrupted` error. A program that neither reads
+    /// nor writes cannot be stopped through the flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_flag` - The flag stopping the program, `None` to disable the check.
+    pub fn set_stop_flag(&mut self, stop_flag: Option<Arc<AtomicBool>>) {
+        self.io.set_stop_flag(stop_flag);
+    }
+
+    /// Function to run the compiled Brainfuck program.
+    ///
+    /// This function finalizes the machine code and executes it, passing in a memory array
+    /// to be used by the Brainfuck program.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if any of the following conditions are met:
+    ///
+    /// * An I/O error occurs while reading from the input stream or writing to the output stream.
+    ///   In this case, a `RuntimeError` is returned containing the underlying I/O error.
+    pub fn run(self) -> Result<(), Error> {
+        match self.cell_width {
+            CellWidth::U8 => self.run_with_buffer(&mut [0u8; MEMORY_SIZE]),
+            cell_width => {
+                let mut memory = vec![0u8; MEMORY_SIZE * cell_width.bytes()];
+                self.run_with_memory(&mut memory)
+            }
+        }
+    }
+
+    /// Function to run the compiled Brainfuck program on a caller-provided memory array.
+    ///
+    /// The memory array is used as is, so the program sees any value already stored in it.
+    /// This allows the same memory array to be reused across runs, see `TapePool`.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory` - The memory array to be used by the Brainfuck program.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if any of the following conditions are met:
+    ///
+    /// * An I/O error occurs while reading from the input stream or writing to the output stream.
+    ///   In this case, a `RuntimeError` is returned containing the underlying I/O error.
+    /// * The program was compiled with `WrapMode::Assume` in a debug build and moves the data
+    ///   pointer out of the memory array. In this case, a `PointerOutOfBounds` error is returned.
+    /// * The stop flag was set, see `Executable::set_stop_flag`. In this case, an `Interrupted`
+    ///   error is returned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the program was compiled with cells wider than a byte, which do
+    /// not fit in the memory array.
+    pub fn run_with_buffer(self, memory: &mut [u8; MEMORY_SIZE]) -> Result<(), Error> {
+        assert_eq!(self.cell_width, CellWidth::U8, "the memory array only holds 8-bit cells");
+        self.run_with_memory(memory)
+    }
+
+    /// Function to run the compiled Brainfuck program on a memory array of `MEMORY_SIZE` cells
+    /// of its `CellWidth`.
+    fn run_with_memory(mut self, memory: &mut [u8]) -> Result<(), Error> {
+        debug_assert_eq!(memory.len(), MEMORY_SIZE * self.cell_width.bytes());
+        let err = unsafe {
+            let exe: backend::Entry = std::mem::transmute(self.code.as_ptr());
+            exe(memory.as_mut_ptr(), &mut *self.io as *mut dyn BufferedOutput as *mut ())
+        };
+        // The output written before an error is flushed too, but the error is the one reported.
+        let flushed = self.io.flush();
+        if !err.is_null() {
+            let err = *unsafe { Box::from_raw(err) };
+            if err.get_ref().is_some_and(|inner| inner.is::<Stopped>()) {
+                return Err(Error::Interrupted);
+            }
+            return Err(match err.get_ref().and_then(|inner| inner.downcast_ref::<OutOfBounds>()) {
+                Some(&OutOfBounds(index)) => Error::PointerOutOfBounds { index },
+                None => Error::RuntimeError(err),
+            });
+        }
+        flushed.map_err(Error::RuntimeError)
+    }
+}
+
+/// Struct representing a pool of memory arrays for running compiled programs.
+///
+/// Allocating and zeroing a fresh memory array for every run is wasteful when many programs are
+/// run one after the other. A `TapePool` hands out zeroed memory arrays and takes them back
+/// once the run is over, zeroing them on return so they are ready for the next run.
+#[derive(Default)]
+pub struct TapePool {
+    /// Zeroed memory arrays ready to be handed out.
+    tapes: Vec<Box<[u8; MEMORY_SIZE]>>,
+}
+
+impl TapePool {
+    /// Function to create a new empty `TapePool`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Function to take a zeroed memory array from the pool, allocating one if it is empty.
+    pub fn acquire(&mut self) -> Box<[u8; MEMORY_SIZE]> {
+        self.tapes
+            .pop()
+            .unwrap_or_else(|| vec![0u8; MEMORY_SIZE].into_boxed_slice().try_into().unwrap())
+    }
+
+    /// Function to give a memory array back to the pool, zeroing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `tape` - The memory array to be reused by later runs.
+    pub fn release(&mut self, mut tape: Box<[u8; MEMORY_SIZE]>) {
+        tape.fill(0);
+        self.tapes.push(tape);
+    }
+
+    /// Function to run a compiled program on a memory array from the pool.
+    ///
+    /// The memory array goes back to the pool when the run is over, even if it fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `executable` - The compiled program to be run.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `Executable::run_with_buffer`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the program was compiled with cells wider than a byte.
+    pub fn run(&mut self, executable: Executable) -> Result<(), Error> {
+        let mut tape = self.acquire();
+        let result = executable.run_with_buffer(&mut tape);
+        self.release(tape);
+        result
+    }
+
+    /// Function to get the number of memory arrays available in the pool.
+    pub fn available(&self) -> usize {
+        self.tapes.len()
+    }
+}
+
+/// Function to compile a Brainfuck program into an `Executable` object.
+///
+/// This function takes a string containing Brainfuck source code, along with input and output
+/// streams, and compiles it into an `Executable` object. It does this by first parsing the source
+/// code into a vector of `Instruction` values using the `parse` function from the `parser` module.
+/// Then, the backend of the target architecture generates machine code for each instruction
+/// using the `dynasm!` macro from the `dynasmrt` crate.
+///
+/// The output of the program is buffered in `OUTPUT_BUFFER_SIZE` bytes, see
+/// `compile_with_output_buffer`.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be compiled.
+/// * `input` - An input stream to be used for reading data into the Brainfuck program.
+/// * `out` - An output stream to be used for writing data from the Brainfuck program.
+///
+/// # Errors
+///
+/// This function returns an error if any of the following conditions are met:
+///
+/// * The given Brainfuck code cannot be parsed successfully. In this case, a `ParseError`
+///   is returned containing the specific parsing error that occurred.
+/// * The memory for the machine code cannot be allocated or made executable. In this case, a
+///   `CompileError` is returned.
+pub fn compile<'a, Input: Read, Output: Write>(
+    source: &str,
+    input: &'a mut Input,
+    out: &'a mut Output,
+) -> Result<Executable<'a>, Error> {
+    compile_with_output_buffer(source, input, out, OUTPUT_BUFFER_SIZE)
+}
+
+/// Function to compile a Brainfuck program into an `Executable` object with an output buffer
+/// of the given size.
+///
+/// The compiled program accumulates its output in a buffer, writing it to the output stream
+/// when it is full, before reading from the input stream and when the program ends. This saves
+/// a write to the output stream per `Write` instruction.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be compiled.
+/// * `input` - An input stream to be used for reading data into the Brainfuck program.
+/// * `out` - An output stream to be used for writing data from the Brainfuck program.
+/// * `buffer_size` - The size in bytes of the output buffer, 0 or 1 to write every byte as soon
+///   as it is produced.
+///
+/// # Errors
+///
+/// This function returns the same errors as `compile`.
+pub fn compile_with_output_buffer<'a, Input: Read, Output: Write>(
+    source: &str,
+    input: &'a mut Input,
+    out: &'a mut Output,
+    buffer_size: usize,
+) -> Result<Executable<'a>, Error> {
+    let options = CompileOptions { output_buffer_size: buffer_size, ..CompileOptions::default() };
+    compile_with_options(source, input, out, options)
+}
+
+/// Function to compile a Brainfuck program into an `Executable` object with the given options.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be compiled.
+/// * `input` - An input stream to be used for reading data into the Brainfuck program.
+/// * `out` - An output stream to be used for writing data from the Brainfuck program.
+/// * `options` - The options of the generated code, see `CompileOptions`.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use headache::compiler::{compile_with_options, CompileOptions, WrapMode};
+///
+/// let mut output = Vec::new();
+/// let options = CompileOptions { wrap_mode: WrapMode::Assume, ..CompileOptions::default() };
+///
+/// compile_with_options(">+++++[<+++++++++++++>-]<.", &mut Cursor::new(b""), &mut output, options)
+///     .unwrap()
+///     .run()
+///     .unwrap();
+///
+/// assert_eq!(&output, b"A");
+/// ```
+///
+/// # Errors
+///
+/// This function returns the same errors as `compile`.
+pub fn compile_with_options<'a, Input: Read, Output: Write>(
+    source: &str,
+    input: &'a mut Input,
+    out: &'a mut Output,
+    options: CompileOptions,
+) -> Result<Executable<'a>, Error> {
+    let parse_options = ParseOptions { opt_level: options.opt_level, ..ParseOptions::default() };
+    let mut instructions = parse_with_options(source, parse_options)?;
+    if options.fold_constant_output && options.cell_width == CellWidth::U8 {
+        instructions = fold_constant_output(instructions);
+    }
+    #[cfg(target_arch="aarch64")]
+    if options.cell_width != CellWidth::U8 {
+        return Err(Error::CompileError(std::io::Error::other("cells wider than a byte are only compiled on x86_64")));
+    }
+    let mut code = dynasmrt::Assembler::<backend::Relocation>::new().map_err(Error::CompileError)?;
+    backend::compile_program(&instructions, &mut code, options.wrap_mode, options.cell_width);
+    drop_instructions(instructions);
+    let code = code.finalize().map_err(|_| {
+        Error::CompileError(std::io::Error::other("the machine code cannot be finalized"))
+    })?;
+    let io = io_context(input, out, options.output_buffer_size, options.cell_width);
+    Ok(Executable::new(code, io, options.cell_width))
+}
+
+/// Function to create the I/O context passed to the machine code.
+///
+/// # Arguments
+///
+/// * `input` - An input stream to be used for reading data into the Brainfuck program.
+/// * `out` - An output stream to be used for writing data from the Brainfuck program.
+/// * `buffer_size` - The size in bytes of the output buffer.
+/// * `cell_width` - The width of the memory cells read and written by the machine code.
+fn io_context<'a, Input: Read, Output: Write>(
+    input: &'a mut Input,
+    out: &'a mut Output,
+    buffer_size: usize,
+    cell_width: CellWidth,
+) -> Box<dyn BufferedOutput + 'a> {
+    let (read, write, write_const) = backend::io_functions::<Input, Output>(cell_width);
+    Box::new(IoContext {
+        read,
+        write,
+        out_of_bounds: backend::out_of_bounds as *const (),
+        write_const,
+        input,
+        out,
+        buffer: Vec::with_capacity(buffer_size),
+        buffer_size,
+        stop_flag: None,
+    })
+}
+
+/// Magic bytes at the start of the files written by `cached_compile`.
+const CACHE_MAGIC: &[u8; 8] = b"HEADACHE";
+
+/// Function to compile a Brainfuck program into an `Executable` object, reusing the machine code
+/// stored in a cache directory by a previous call.
+///
+/// The machine code of the program is looked up in a `.bin` file of `cache_dir` named after a
+/// hash of the source code. If the file does not exist, the program is compiled with the default
+/// options, like `compile` does, and its machine code is stored in a new file. The file records
+/// the target architecture, the version of the crate and the source code itself, as the hash does
+/// not tell programs apart for sure. A file written by another architecture or version, for
+/// another program, or one that cannot be read, is ignored and overwritten.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be compiled.
+/// * `cache_dir` - The directory where the machine code is stored, created if it does not exist.
+/// * `input` - An input stream to be used for reading data into the Brainfuck program.
+/// * `out` - An output stream to be used for writing data from the Brainfuck program.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use headache::compiler::cached_compile;
+///
+/// let cache_dir = std::env::temp_dir().join("headache_doc_cache");
+/// let (mut input, mut output) = (Cursor::new(b""), Vec::new());
+///
+/// cached_compile("++++++++[>++++++++<-]>+.", &cache_dir, &mut input, &mut output)
+///     .unwrap()
+///     .run()
+///     .unwrap();
+///
+/// assert_eq!(&output, b"A");
+/// ```
+///
+/// # Errors
+///
+/// This function returns the same errors as `compile`, and a `CompileError` if the cache
+/// directory or the file cannot be written.
+pub fn cached_compile<'a, Input: Read, Output: Write>(
+    source: &str,
+    cache_dir: impl AsRef<std::path::Path>,
+    input: &'a mut Input,
+    out: &'a mut Output,
+) -> Result<Executable<'a>, Error> {
+    let cache_dir = cache_dir.as_ref();
+    let path = cache_dir.join(format!("{:016x}.bin", source_hash(source)));
+    if let Some(code) = std::fs::read(&path).ok().and_then(|file| load_cached_code(&file, source)) {
+        return Ok(Executable::new(code, io_context(input, out, OUTPUT_BUFFER_SIZE, CellWidth::U8), CellWidth::U8));
+    }
+    let executable = compile(source, input, out)?;
+    let machine_code = executable.machine_code();
+    let mut file = Vec::with_capacity(CACHE_MAGIC.len() + source.len() + machine_code.len() + 64);
+    file.extend_from_slice(CACHE_MAGIC);
+    for field in [std::env::consts::ARCH.as_bytes(), env!("CARGO_PKG_VERSION").as_bytes(), source.as_bytes(), machine_code] {
+        file.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        file.extend_from_slice(field);
+    }
+    // The file is renamed into place so that a concurrent call never reads it half written.
+    let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::create_dir_all(cache_dir)
+        .and_then(|_| std::fs::write(&temporary, &file))
+        .and_then(|_| std::fs::rename(&temporary, &path))
+        .map_err(Error::CompileError)?;
+    Ok(executable)
+}
+
+/// Function to hash Brainfuck source code with the 64-bit FNV-1a algorithm, which unlike the
+/// hasher of the standard library gives the same value in every build.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code.
+fn source_hash(source: &str) -> u64 {
+    source.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Function to load the machine code stored in a file written by `cached_compile` into
+/// executable memory.
+///
+/// # Arguments
+///
+/// * `file` - The content of the file.
+/// * `source` - The Brainfuck source code the machine code must have been compiled from.
+///
+/// # Returns
+///
+/// The machine code, or `None` if the file is malformed, was written for another architecture,
+/// by another version of the crate or for another program, or the memory cannot be made
+/// executable.
+fn load_cached_code(file: &[u8], source: &str) -> Option<dynasmrt::ExecutableBuffer> {
+    let mut rest = file.strip_prefix(CACHE_MAGIC)?;
+    let mut field = || {
+        let (len, tail) = rest.split_first_chunk::<8>()?;
+        let len = usize::try_from(u64::from_le_bytes(*len)).ok()?;
+        let (field, tail) = tail.split_at_checked(len)?;
+        rest = tail;
+        Some(field)
+    };
+    if field()? != std::env::consts::ARCH.as_bytes() || field()? != env!("CARGO_PKG_VERSION").as_bytes() {
+        return None;
+    }
+    if field()? != source.as_bytes() {
+        return None;
+    }
+    let machine_code = field()?;
+    if machine_code.is_empty() || !rest.is_empty() {
+        return None;
+    }
+    let mut buffer = dynasmrt::mmap::MutableBuffer::new(machine_code.len()).ok()?;
+    buffer.set_len(machine_code.len());
+    buffer.copy_from_slice(machine_code);
+    buffer.make_exec().ok()
+}
+
+/// Struct representing the state through which the machine code reads and writes data.
+///
+/// The machine code gets a pointer to it and calls the functions at its start with the pointer
+/// of the current cell and the pointer to the context, so their layout must not change.
+#[repr(C)]
+struct IoContext<'a, Input, Output> {
+    /// Address of the function reading a cell, see `IoContext::read_cell`.
+    read: *const (),
+    /// Address of the function writing a cell, see `IoContext::write_bytes`.
+    write: *const (),
+    /// Address of the function reporting that the data pointer moved out of the memory array.
+    out_of_bounds: *const (),
+    /// Address of the function writing a constant string of bytes, see `IoContext::write_bytes`.
+    write_const: *const (),
+    /// Input stream of the Brainfuck program.
+    input: &'a mut Input,
+    /// Output stream of the Brainfuck program.
+    out: &'a mut Output,
+    /// Output not yet written to the output stream.
+    buffer: Vec<u8>,
+    /// Number of bytes of output that triggers a write to the output stream.
+    buffer_size: usize,
+    /// Flag that stops the program when it is set, see `Executable::set_stop_flag`.
+    stop_flag: Option<Arc<AtomicBool>>,
+}
+
+impl<Input: Read, Output: Write> IoContext<'_, Input, Output> {
+    /// Function to read the bytes of a cell from the input stream, after writing the buffered output.
+    fn read_cell(&mut self, cell: &mut [u8]) -> std::io::Result<()> {
+        self.flush()?;
+        self.check_stop_flag()?;
+        self.input.read_exact(cell)
+    }
+
+    /// Function to buffer a string of bytes of output, writing the buffer to the output stream
+    /// if it is full.
+    fn write_bytes(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.buffer.extend_from_slice(bytes);
+        // The output is written by `Executable` before reporting the interruption.
+        self.check_stop_flag()?;
+        if self.buffer.len() >= self.buffer_size {
+            self.out.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    /// Function to return a `Stopped` error if the stop flag is set.
+    fn check_stop_flag(&self) -> std::io::Result<()> {
+        match &self.stop_flag {
+            Some(stop_flag) if stop_flag.load(Ordering::Relaxed) => Err(std::io::Error::other(Stopped)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Struct representing the index of a data pointer that moved out of the memory array, carried
+/// by the `std::io::Error` returned from the machine code.
+#[derive(Debug)]
+struct OutOfBounds(isize);
+
+impl std::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the data pointer moved out of the memory array (index {})", self.0)
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+/// Struct representing the stop of a program through its stop flag, carried by the
+/// `std::io::Error` returned from the machine code.
+#[derive(Debug)]
+struct Stopped;
+
+impl std::fmt::Display for Stopped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the program was stopped through its stop flag")
+    }
+}
+
+impl std::error::Error for Stopped {}
+
+/// Trait representing an I/O context whose buffered output can be written out, so an
+/// `Executable` can hold an `IoContext` without its stream types.
+trait BufferedOutput {
+    /// Function to write the buffered output to the output stream and flush it.
+    fn flush(&mut self) -> std::io::Result<()>;
+
+    /// Function to set the flag that stops the program, see `Executable::set_stop_flag`.
+    fn set_stop_flag(&mut self, stop_flag: Option<Arc<AtomicBool>>);
+}
+
+impl<Input: Read, Output: Write> BufferedOutput for IoContext<'_, Input, Output> {
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.write_all(&self.buffer)?;
+        self.buffer.clear();
+        self.out.flush()
+    }
+
+    fn set_stop_flag(&mut self, stop_flag: Option<Arc<AtomicBool>>) {
+        self.stop_flag = stop_flag;
+    }
+}
+
+#[cfg(target_arch="x86_64")]
+/// Function to compile a Brainfuck program ahead of time into an ELF object file.
+///
+/// The object file defines a `main` function that runs the program on a zeroed memory array
+/// allocated on the stack, so it can be linked into a standalone Linux executable with
+/// `cc program.o -o program`. The program reads from the standard input and writes to the
+/// standard output through the Linux `read` and `write` system calls. `main` returns 0 when the
+/// program finishes, or 1 if a read or write fails or the input ends.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be compiled.
+/// * `path` - The path of the object file to be written.
+///
+/// # Errors
+///
+/// This function returns an error if any of the following conditions are met:
+///
+/// * The given Brainfuck code cannot be parsed successfully. In this case, a `ParseError`
+///   is returned containing the specific parsing error that occurred.
+/// * The machine code cannot be generated or the object file cannot be written. In this case,
+///   a `CompileError` is returned containing the underlying I/O error.
+pub fn compile_to_object(source: &str, path: impl AsRef<Path>) -> Result<(), Error> {
+    let instructions = parse(source)?;
+    // The memory array of `main` is always zeroed, so the output can be folded.
+    let instructions = fold_constant_output(instructions);
+    let text = x64::compile_standalone(&instructions);
+    drop_instructions(instructions);
+    let text = text?;
+    std::fs::write(path, elf::relocatable_object(&text, "main")).map_err(Error::CompileError)
+}
+
+#[cfg(target_arch="x86_64")]
+/// Function to produce a human-readable x86-64 assembly listing of a Brainfuck program.
+///
+/// The listing mirrors the machine code generated by `compile` instruction by instruction, with
+/// a comment naming every instruction and numbered labels for loops. The listing is a debugging
+/// aid, it is not meant to be assembled by an external tool.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be listed.
+///
+/// # Errors
+///
+/// This function returns a `ParseError` if the given Brainfuck code cannot be parsed.
+pub fn compile_to_asm(source: &str) -> Result<String, Error> {
+    let instructions = parse(source)?;
+    let listing = x64::listing(&instructions);
+    drop_instructions(instructions);
+    Ok(listing)
+}
@@ -0,0 +1,826 @@
+use dynasmrt::{dynasm, x64::X64Relocation, Assembler, DynamicLabel, DynasmApi, DynasmLabelApi};
+
+use super::{CellWidth, IoContext, OutOfBounds, WrapMode};
+use crate::MEMORY_SIZE;
+use crate::{error::Error, instruction::Instruction};
+use std::io::{Read, Write};
+
+/// Type of the relocations of the x86-64 machine code.
+pub(super) type Relocation = X64Relocation;
+
+/// Type of the function generated by `compile_program`.
+pub(super) type Entry = unsafe extern "sysv64" fn(*mut u8, *mut ()) -> *mut std::io::Error;
+
+/// Function to generate the machine code of a Brainfuck program run in-process by `Executable`.
+///
+/// The generated function follows the sysv64 calling convention: it takes the pointer to the
+/// memory array in `rdi` and the pointer to the `IoContext` in `rsi`, and returns a null pointer
+/// or a pointer to an `std::io::Error` in `rax`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be compiled.
+/// * `code` - An `Assembler` object from the `dynasmrt` crate to which the generated machine code
+///   will be added.
+/// * `wrap` - How the generated machine code moves the data pointer, see `WrapMode`.
+/// * `cell_width` - The width of the memory cells, see `CellWidth`.
+pub(super) fn compile_program(
+    instructions: &[Instruction],
+    code: &mut Assembler<X64Relocation>,
+    wrap: WrapMode,
+    cell_width: CellWidth,
+) {
+    dynasm! { code
+        ; .arch x64
+        ; push rbp
+        ; mov rbp, rsp
+        ; push r12 // pointer to memory
+        ; push r13 // offset from r12
+        ; push r14 // pointer to the I/O context
+        ; sub rsp, 8 // keep the stack aligned for calls
+        ; mov r12, rdi
+        ; mov r14, rsi
+        ; xor r13, r13
+    };
+
+    compile_segment(instructions, code, &Io::Context, wrap, cell_width);
+
+    dynasm! { code
+        ; .arch x64
+        ; xor rax, rax // clear return register if not early return
+        ; ->exit:
+        ; add rsp, 8
+        ; pop r14
+        ; pop r13
+        ; pop r12
+        ; pop rbp
+        ; ret
+    }
+
+    if checks_bounds(wrap) {
+        dynasm! { code
+            ; .arch x64
+            ; ->out_of_bounds:
+            ; mov     rdi, rcx
+            ; call    QWORD [r14 + 16]
+            ; jmp     ->exit
+        }
+    }
+}
+
+/// Function to generate the machine code of a standalone `main` function running a Brainfuck program.
+///
+/// See `compile_to_object`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be compiled.
+///
+/// # Errors
+///
+/// This function returns a `CompileError` if the machine code cannot be generated.
+pub(super) fn compile_standalone(instructions: &[Instruction]) -> Result<Vec<u8>, Error> {
+    let mut code = Assembler::<X64Relocation>::new().map_err(Error::CompileError)?;
+
+    dynasm! { code
+        ; .arch x64
+        ; push rbp
+        ; mov rbp, rsp
+        ; push r12 // pointer to memory
+        ; push r13 // offset from r12
+        ; sub rsp, MEMORY_SIZE as i32
+        ; mov r12, rsp
+        ; mov rdi, rsp
+        ; xor eax, eax
+        ; mov ecx, MEMORY_SIZE as i32
+        ; rep stosb // zero the memory array
+        ; xor r13, r13
+    };
+
+    compile_segment(instructions, &mut code, &Io::Syscalls, WrapMode::Wrap, CellWidth::U8);
+
+    dynasm! { code
+        ; .arch x64
+        ; xor eax, eax // return 0 if not early return
+        ; ->exit:
+        ; lea rsp, [rbp - 16]
+        ; pop r13
+        ; pop r12
+        ; pop rbp
+        ; ret
+        ; ->io_error:
+        ; mov eax, 1
+        ; jmp ->exit
+    }
+
+    let text = code.finalize().map_err(|_| {
+        Error::CompileError(std::io::Error::other("the machine code cannot be finalized"))
+    })?;
+    Ok(text.to_vec())
+}
+
+/// Function to produce the assembly listing of a Brainfuck program, see `compile_to_asm`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be listed.
+pub(super) fn listing(instructions: &[Instruction]) -> String {
+    let mut asm = String::new();
+
+    push_lines(&mut asm, &[
+        "push    rbp",
+        "mov     rbp, rsp",
+        "push    r12",
+        "push    r13",
+        "push    r14",
+        "sub     rsp, 8",
+        "mov     r12, rdi",
+        "mov     r14, rsi",
+        "xor     r13, r13",
+    ]);
+    asm_segment(instructions, &mut asm, &mut 0);
+    push_lines(&mut asm, &["xor     rax, rax"]);
+    asm.push_str("exit:\n");
+    push_lines(&mut asm, &["add     rsp, 8", "pop     r14", "pop     r13", "pop     r12", "pop     rbp", "ret"]);
+    asm
+}
+
+/// Function to append the assembly listing of a segment of Brainfuck instructions.
+///
+/// This function mirrors `compile_segment`, any change to the generated machine code must be
+/// reflected here.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be listed.
+/// * `asm` - The listing to which the assembly will be appended.
+/// * `labels` - The number of loop labels used so far, to keep them unique.
+fn asm_segment(instructions: &[Instruction], asm: &mut String, labels: &mut usize) {
+    for instruction in instructions {
+        match instruction {
+            Instruction::Loop(_) => {}
+            instruction => asm.push_str(&format!("; {instruction:?}\n")),
+        }
+        match instruction {
+            Instruction::Move(n) => {
+                asm_offset_index(*n, asm);
+                push_lines(asm, &["mov     r13, rcx"]);
+            }
+            Instruction::Add(n) => {
+                push_lines(asm, &[&format!("add     BYTE [r12 + r13], {}", *n as i8)]);
+            }
+            Instruction::AddAt { offset, value } => {
+                asm_offset_index(*offset, asm);
+                push_lines(asm, &[&format!("add     BYTE [r12 + rcx], {}", *value as i8)]);
+            }
+            Instruction::Write => {
+                push_lines(asm, &[
+                    "lea     rdi, [r12 + r13]",
+                    "mov     rsi, r14",
+                    "call    QWORD [r14 + 8]",
+                    "cmp     rax, 0",
+                    "jne     exit",
+                ]);
+            }
+            Instruction::Read => {
+                push_lines(asm, &[
+                    "lea     rdi, [r12 + r13]",
+                    "mov     rsi, r14",
+                    "call    QWORD [r14]",
+                    "cmp     rax, 0",
+                    "jne     exit",
+                ]);
+            }
+            Instruction::Loop(loop_segment) => {
+                let label = *labels;
+                *labels += 1;
+                asm.push_str(&format!("loop_{label}:\n"));
+                push_lines(asm, &["cmp     BYTE [r12 + r13], 0", &format!("je      end_{label}")]);
+                asm_segment(loop_segment, asm, labels);
+                push_lines(asm, &["cmp     BYTE [r12 + r13], 0", &format!("jne     loop_{label}")]);
+                asm.push_str(&format!("end_{label}:\n"));
+            }
+            Instruction::Clear => {
+                push_lines(asm, &["mov     BYTE [r12 + r13], 0"]);
+            }
+            Instruction::Set(n) => {
+                push_lines(asm, &[&format!("mov     BYTE [r12 + r13], {n}")]);
+            }
+            Instruction::AddTo { offset } => {
+                asm_offset_index(*offset, asm);
+                push_lines(asm, &["mov     al, BYTE [r12 + r13]", "add     BYTE [r12 + rcx], al", "mov     BYTE [r12 + r13], 0"]);
+            }
+            Instruction::ClearScan { stride } => {
+                asm_segment(&[Instruction::Loop(vec![Instruction::Clear, Instruction::Move(*stride)])], asm, labels)
+            }
+            Instruction::Seek { stride } => {
+                let label = *labels;
+                *labels += 1;
+                asm.push_str(&format!("loop_{label}:\n"));
+                push_lines(asm, &["cmp     BYTE [r12 + r13], 0", &format!("je      end_{label}")]);
+                asm_offset_index(*stride, asm);
+                push_lines(asm, &["mov     r13, rcx", &format!("jmp     loop_{label}")]);
+                asm.push_str(&format!("end_{label}:\n"));
+            }
+            Instruction::MultiplyInto { targets } => {
+                push_lines(asm, &["movzx   r8d, BYTE [r12 + r13]"]);
+                for &(offset, factor) in targets {
+                    asm_offset_index(offset, asm);
+                    push_lines(asm, &[&format!("imul    eax, r8d, {factor}"), "add     BYTE [r12 + rcx], al"]);
+                }
+                push_lines(asm, &["mov     BYTE [r12 + r13], 0"]);
+            }
+            Instruction::DebugDump => {}
+            Instruction::WriteConst(constant) => {
+                let label = *labels;
+                *labels += 1;
+                push_lines(asm, &[&format!("jmp     after_{label}")]);
+                let bytes: Vec<String> = constant.iter().map(u8::to_string).collect();
+                asm.push_str(&format!("constant_{label}:\n"));
+                push_lines(asm, &[&format!("db      {}", bytes.join(", "))]);
+                asm.push_str(&format!("after_{label}:\n"));
+                push_lines(asm, &[
+                    &format!("lea     rdi, [constant_{label}]"),
+                    &format!("mov     rsi, QWORD {}", constant.len()),
+                    "mov     rdx, r14",
+                    "call    QWORD [r14 + 24]",
+                    "cmp     rax, 0",
+                    "jne     exit",
+                ]);
+            }
+        }
+    }
+}
+
+/// Function to append the assembly listing of the code generated by `compile_offset_index`.
+fn asm_offset_index(offset: isize, asm: &mut String) {
+    push_lines(asm, &[
+        &format!("mov     rcx, QWORD {offset}"),
+        "mov     rdx, QWORD 5037190915060954895",
+        "mov     rax, rcx",
+        "imul    rdx",
+        "mov     rax, rdx",
+        "shr     rax, 63",
+        "sar     rdx, 13",
+        "add     rdx, rax",
+        "imul    rax, rdx, 30000",
+        "sub     rcx, rax",
+        "mov     rax, r13",
+        "add     rcx, rax",
+        "add     rcx, 30000",
+        "mov     rax, rcx",
+        "shr     rax, 4",
+        "mov     rdx, QWORD 314824432191309681",
+        "mul     rdx",
+        "shr     rdx, 5",
+        "imul    rax, rdx, 30000",
+        "sub     rcx, rax",
+    ]);
+}
+
+/// Function to append indented assembly lines to a listing.
+fn push_lines(asm: &mut String, lines: &[&str]) {
+    for line in lines {
+        asm.push_str("    ");
+        asm.push_str(line);
+        asm.push('\n');
+    }
+}
+
+/// Function to generate machine code for a segment of Brainfuck instructions.
+///
+/// This function takes a slice of `Instruction` values and generates machine code for each instruction
+/// using the `dynasm!` macro from the `dynasmrt` crate. It is a helper function used by
+/// `compile_program` and `compile_standalone`. The code generated for every instruction with
+/// 8-bit cells must be mirrored by `asm_segment`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be compiled.
+/// * `code` - An `Assembler` object from the `dynasmrt` crate to which the generated machine code
+///   will be added.
+/// * `io` - How the generated machine code reads and writes data, the system calls only read
+///   and write 8-bit cells.
+/// * `wrap` - How the generated machine code moves the data pointer, see `WrapMode`.
+/// * `width` - The width of the memory cells, see `CellWidth`.
+fn compile_segment(
+    instructions: &[Instruction],
+    code: &mut Assembler<X64Relocation>,
+    io: &Io,
+    wrap: WrapMode,
+    width: CellWidth,
+) {
+    for instruction in instructions {
+        match instruction {
+            Instruction::Move(n) => {
+                compile_offset_index(*n, code, wrap);
+                dynasm! { code
+                    ; .arch x64
+                    ; mov     r13, rcx
+                }
+            }
+            Instruction::Add(n) => compile_cell_add(*n, code, width),
+            Instruction::AddAt { offset, value } => {
+                compile_offset_index(*offset, code, wrap);
+                compile_cell_add_at(*value, code, width);
+            }
+            Instruction::Write => match io {
+                Io::Context => {
+                    compile_cell_address(code, width);
+                    dynasm! { code
+                        ; .arch x64
+                        ; mov     rsi, r14
+                        ; call    QWORD [r14 + 8]
+                        ; cmp     rax, 0
+                        ; jne     ->exit
+                    }
+                }
+                Io::Syscalls => {
+                    dynasm! { code
+                        ; .arch x64
+                        ; mov     eax, 1 // write
+                        ; mov     edi, 1 // stdout
+                        ; lea     rsi, [r12 + r13]
+                        ; mov     edx, 1
+                        ; syscall
+                        ; cmp     rax, 1
+                        ; jne     ->io_error
+                    }
+                }
+            },
+            Instruction::Read => match io {
+                Io::Context => {
+                    compile_cell_address(code, width);
+                    dynasm! { code
+                        ; .arch x64
+                        ; mov     rsi, r14
+                        ; call    QWORD [r14]
+                        ; cmp     rax, 0
+                        ; jne     ->exit
+                    }
+                }
+                Io::Syscalls => {
+                    dynasm! { code
+                        ; .arch x64
+                        ; mov     eax, 0 // read
+                        ; mov     edi, 0 // stdin
+                        ; lea     rsi, [r12 + r13]
+                        ; mov     edx, 1
+                        ; syscall
+                        ; cmp     rax, 1
+                        ; jne     ->io_error
+                    }
+                }
+            },
+            Instruction::Loop(loop_segment) => {
+                let loop_label = code.new_dynamic_label();
+                let end_label = code.new_dynamic_label();
+                dynasm! { code
+                    ; .arch x64
+                    ; =>loop_label
+                }
+                compile_cell_test(code, width);
+                dynasm! { code
+                    ; .arch x64
+                    ; je      =>end_label
+                }
+                compile_segment(loop_segment, code, io, wrap, width);
+                compile_cell_test(code, width);
+                dynasm! { code
+                    ; .arch x64
+                    ; jne     =>loop_label
+                    ; =>end_label
+                }
+            }
+            Instruction::Clear => compile_cell_set(0, code, width),
+            Instruction::Set(n) => compile_cell_set(*n, code, width),
+            Instruction::AddTo { offset } => {
+                let skip_label = compile_skip_if_zero(code, wrap, width);
+                compile_offset_index(*offset, code, wrap);
+                compile_cell_add_to(code, width);
+                compile_cell_set(0, code, width);
+                compile_skip_label(skip_label, code);
+            }
+            Instruction::ClearScan { stride } => {
+                let clear_scan = [Instruction::Loop(vec![Instruction::Clear, Instruction::Move(*stride)])];
+                compile_segment(&clear_scan, code, io, wrap, width)
+            }
+            Instruction::Seek { stride } => {
+                let loop_label = code.new_dynamic_label();
+                let end_label = code.new_dynamic_label();
+                dynasm! { code
+                    ; .arch x64
+                    ; =>loop_label
+                }
+                compile_cell_test(code, width);
+                dynasm! { code
+                    ; .arch x64
+                    ; je      =>end_label
+                }
+                compile_offset_index(*stride, code, wrap);
+                dynasm! { code
+                    ; .arch x64
+                    ; mov     r13, rcx
+                    ; jmp     =>loop_label
+                    ; =>end_label
+                }
+            }
+            Instruction::MultiplyInto { targets } => {
+                let skip_label = compile_skip_if_zero(code, wrap, width);
+                compile_cell_load(code, width);
+                for &(offset, factor) in targets {
+                    compile_offset_index(offset, code, wrap);
+                    compile_cell_add_product(factor, code, width);
+                }
+                compile_cell_set(0, code, width);
+                compile_skip_label(skip_label, code);
+            }
+            // Compiled programs do not dump the memory, only the `Executor` does.
+            Instruction::DebugDump => {}
+            Instruction::WriteConst(constant) => {
+                // The string is embedded in the machine code, jumped over.
+                dynasm! { code
+                    ; .arch x64
+                    ; jmp     >after
+                    ; constant:
+                    ; .bytes  constant.iter()
+                    ; after:
+                }
+                match io {
+                    Io::Context => {
+                        dynasm! { code
+                            ; .arch x64
+                            ; lea     rdi, [<constant]
+                            ; mov     rsi, QWORD constant.len() as i64
+                            ; mov     rdx, r14
+                            ; call    QWORD [r14 + 24]
+                            ; cmp     rax, 0
+                            ; jne     ->exit
+                        }
+                    }
+                    Io::Syscalls => {
+                        dynasm! { code
+                            ; .arch x64
+                            ; mov     eax, 1 // write
+                            ; mov     edi, 1 // stdout
+                            ; lea     rsi, [<constant]
+                            ; mov     rdx, QWORD constant.len() as i64
+                            ; syscall
+                            ; cmp     rax, rdx
+                            ; jne     ->io_error
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Function to generate machine code skipping an `AddTo` or `MultiplyInto` instruction when the
+/// current cell is zero, which is only needed for `WrapMode::Assume`.
+///
+/// The loop replaced by those instructions does not run when the current cell is zero, so a
+/// program may rely on it not touching its targets then, even if they are out of the memory array.
+///
+/// # Returns
+///
+/// * The label to be placed after the instruction with `compile_skip_label`, or None if nothing
+///   was generated.
+fn compile_skip_if_zero(code: &mut Assembler<X64Relocation>, wrap: WrapMode, width: CellWidth) -> Option<DynamicLabel> {
+    if wrap != WrapMode::Assume {
+        return None;
+    }
+    let skip_label = code.new_dynamic_label();
+    compile_cell_test(code, width);
+    dynasm! { code
+        ; .arch x64
+        ; je      =>skip_label
+    }
+    Some(skip_label)
+}
+
+/// Function to place the label returned by `compile_skip_if_zero`, if any.
+fn compile_skip_label(skip_label: Option<DynamicLabel>, code: &mut Assembler<X64Relocation>) {
+    if let Some(skip_label) = skip_label {
+        dynasm! { code
+            ; .arch x64
+            ; =>skip_label
+        }
+    }
+}
+
+/// Function to generate machine code comparing the current cell with 0, setting the flags.
+fn compile_cell_test(code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; cmp     BYTE [r12 + r13], 0
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; cmp     WORD [r12 + r13 * 2], 0
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; cmp     DWORD [r12 + r13 * 4], 0
+        },
+    }
+}
+
+/// Function to generate machine code adding the delta of an `Add` instruction to the current
+/// cell, sign extended to the cell width.
+fn compile_cell_add(delta: u8, code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; add     BYTE [r12 + r13], delta as i8
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; add     WORD [r12 + r13 * 2], delta as i8 as i16
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; add     DWORD [r12 + r13 * 4], delta as i8 as i32
+        },
+    }
+}
+
+/// Function to generate machine code adding an 8-bit two's complement delta to the cell whose
+/// index is in `rcx`.
+fn compile_cell_add_at(delta: u8, code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; add     BYTE [r12 + rcx], delta as i8
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; add     WORD [r12 + rcx * 2], delta as i8 as i16
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; add     DWORD [r12 + rcx * 4], delta as i8 as i32
+        },
+    }
+}
+
+/// Function to generate machine code setting the current cell to the value that adding an
+/// 8-bit two's complement delta to 0 gives, 0 for a `Clear` instruction.
+fn compile_cell_set(delta: u8, code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; mov     BYTE [r12 + r13], delta as i8
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; mov     WORD [r12 + r13 * 2], delta as i8 as i16
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; mov     DWORD [r12 + r13 * 4], delta as i8 as i32
+        },
+    }
+}
+
+/// Function to generate machine code adding the current cell to the cell whose index is in
+/// `rcx`, overwriting `rax`.
+fn compile_cell_add_to(code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; mov     al, BYTE [r12 + r13]
+            ; add     BYTE [r12 + rcx], al
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; mov     ax, WORD [r12 + r13 * 2]
+            ; add     WORD [r12 + rcx * 2], ax
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; mov     eax, DWORD [r12 + r13 * 4]
+            ; add     DWORD [r12 + rcx * 4], eax
+        },
+    }
+}
+
+/// Function to generate machine code loading the current cell into `r8d`, zero extended, for
+/// `compile_cell_add_product`.
+fn compile_cell_load(code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; movzx   r8d, BYTE [r12 + r13]
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; movzx   r8d, WORD [r12 + r13 * 2]
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; mov     r8d, DWORD [r12 + r13 * 4]
+        },
+    }
+}
+
+/// Function to generate machine code adding the cell loaded by `compile_cell_load` times the
+/// factor of a `MultiplyInto` instruction, sign extended to the cell width, to the cell whose
+/// index is in `rcx`, overwriting `rax`.
+fn compile_cell_add_product(factor: u8, code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; imul    eax, r8d, factor as i32
+            ; add     BYTE [r12 + rcx], al
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; imul    eax, r8d, factor as i8 as i32
+            ; add     WORD [r12 + rcx * 2], ax
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; imul    eax, r8d, factor as i8 as i32
+            ; add     DWORD [r12 + rcx * 4], eax
+        },
+    }
+}
+
+/// Function to generate machine code loading the address of the current cell into `rdi`, to be
+/// passed to the functions of the `IoContext`.
+fn compile_cell_address(code: &mut Assembler<X64Relocation>, width: CellWidth) {
+    match width {
+        CellWidth::U8 => dynasm! { code
+            ; .arch x64
+            ; lea     rdi, [r12 + r13]
+        },
+        CellWidth::U16 => dynasm! { code
+            ; .arch x64
+            ; lea     rdi, [r12 + r13 * 2]
+        },
+        CellWidth::U32 => dynasm! { code
+            ; .arch x64
+            ; lea     rdi, [r12 + r13 * 4]
+        },
+    }
+}
+
+/// Enum representing how the generated machine code performs the `Read` and `Write` instructions.
+enum Io {
+    /// Calling the functions of the `IoContext` pointed by `r14`, for code run in-process.
+    Context,
+    /// Calling the Linux system calls on the standard input and output, for standalone code.
+    Syscalls,
+}
+
+/// Function to get the addresses of the functions called by the machine code to read and write
+/// a cell and to write a constant string, to be stored in the `IoContext`.
+///
+/// # Arguments
+///
+/// * `cell_width` - The width of the cells read and written.
+pub(super) fn io_functions<Input: Read, Output: Write>(cell_width: CellWidth) -> (*const (), *const (), *const ()) {
+    let (read, write) = match cell_width {
+        CellWidth::U8 => (read::<Input, Output, 1> as *const (), write::<Input, Output, 1> as *const ()),
+        CellWidth::U16 => (read::<Input, Output, 2> as *const (), write::<Input, Output, 2> as *const ()),
+        CellWidth::U32 => (read::<Input, Output, 4> as *const (), write::<Input, Output, 4> as *const ()),
+    };
+    (read, write, write_const::<Input, Output> as *const ())
+}
+
+/// Function to read the `BYTES` bytes of a cell from the input stream of an I/O context.
+///
+/// This function is an extern "sysv64" function that is called by the generated machine code to read the little-endian bytes of a cell from the input stream and store them in the given memory location. It returns a null pointer if the read is successful or a pointer to an `std::io::Error` object if an error occurs.
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the memory location where the read bytes should be stored.
+/// * `io` - A pointer to the I/O context holding the input stream from which to read the bytes.
+extern "sysv64" fn read<Input: Read, Output: Write, const BYTES: usize>(
+    ptr: &mut [u8; BYTES],
+    io: &mut IoContext<Input, Output>,
+) -> *mut std::io::Error {
+    match io.read_cell(ptr) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(err) => Box::into_raw(Box::new(err)),
+    }
+}
+
+/// Function to write the `BYTES` bytes of a cell to the output stream of an I/O context.
+///
+/// This function is an extern "sysv64" function that is called by the generated machine code to write the little-endian bytes of a cell from the given memory location to the output stream. It returns a null pointer if the write is successful or a pointer to an `std::io::Error` object if an error occurs.
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the memory location containing the bytes to be written.
+/// * `io` - A pointer to the I/O context holding the output stream to which the bytes should be written.
+extern "sysv64" fn write<Input: Read, Output: Write, const BYTES: usize>(
+    ptr: &mut [u8; BYTES],
+    io: &mut IoContext<Input, Output>,
+) -> *mut std::io::Error {
+    match io.write_bytes(ptr) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(err) => Box::into_raw(Box::new(err)),
+    }
+}
+
+/// Function to write a constant string of bytes to the output stream of an I/O context.
+///
+/// This function is an extern "sysv64" function that is called by the generated machine code for a `WriteConst` instruction. It returns a null pointer if the write is successful or a pointer to an `std::io::Error` object if an error occurs.
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the first byte of the string, embedded in the machine code.
+/// * `len` - The number of bytes of the string.
+/// * `io` - A pointer to the I/O context holding the output stream to which the bytes should be written.
+extern "sysv64" fn write_const<Input: Read, Output: Write>(
+    ptr: *const u8,
+    len: usize,
+    io: &mut IoContext<Input, Output>,
+) -> *mut std::io::Error {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+    match io.write_bytes(bytes) {
+        Ok(_) => std::ptr::null_mut(),
+        Err(err) => Box::into_raw(Box::new(err)),
+    }
+}
+
+/// Function to generate machine code computing the index `offset` cells away from the data pointer.
+///
+/// The index is left in `rcx`. With `WrapMode::Wrap` it wraps around the memory array boundaries
+/// and the generated code overwrites `rax` and `rdx`. With `WrapMode::Assume` it is a plain sum,
+/// checked against the memory array boundaries in debug builds.
+///
+/// # Arguments
+///
+/// * `offset` - The distance from the data pointer, it can be negative.
+/// * `code` - An `Assembler` object from the `dynasmrt` crate to which the generated machine code
+///   will be added.
+/// * `wrap` - How the index is computed.
+fn compile_offset_index(offset: isize, code: &mut Assembler<X64Relocation>, wrap: WrapMode) {
+    if wrap == WrapMode::Assume {
+        match i32::try_from(offset) {
+            Ok(offset) => dynasm! { code
+                ; .arch x64
+                ; lea     rcx, [r13 + offset]
+            },
+            Err(_) => dynasm! { code
+                ; .arch x64
+                ; mov     rcx, QWORD offset as _
+                ; add     rcx, r13
+            },
+        }
+        if checks_bounds(wrap) {
+            dynasm! { code
+                ; .arch x64
+                ; cmp     rcx, MEMORY_SIZE as i32
+                ; jae     ->out_of_bounds
+            }
+        }
+        return;
+    }
+    dynasm! { code
+        ; .arch x64
+        ; mov     rcx, QWORD offset as _
+        ; mov     rdx, QWORD 5037190915060954895
+        ; mov     rax, rcx
+        ; imul    rdx
+        ; mov     rax, rdx
+        ; shr     rax, 63
+        ; sar     rdx, 13
+        ; add     rdx, rax
+        ; imul    rax, rdx, 30000
+        ; sub     rcx, rax
+        ; mov     rax, r13
+        ; add     rcx, rax
+        ; add     rcx, 30000
+        ; mov     rax, rcx
+        ; shr     rax, 4
+        ; mov     rdx, QWORD 314824432191309681
+        ; mul     rdx
+        ; shr     rdx, 5
+        ; imul    rax, rdx, 30000
+        ; sub     rcx, rax
+    }
+}
+
+/// Function to tell whether the generated machine code checks the data pointer against the
+/// memory array boundaries, which is only done for `WrapMode::Assume` in debug builds.
+fn checks_bounds(wrap: WrapMode) -> bool {
+    wrap == WrapMode::Assume && cfg!(debug_assertions)
+}
+
+/// Function to report that the data pointer moved out of the memory array.
+///
+/// This function is an extern "sysv64" function that is called by the generated machine code
+/// when a bounds check fails, see `WrapMode::Assume`. It returns a pointer to an `std::io::Error`
+/// carrying the index, turned into a `PointerOutOfBounds` error by `Executable`.
+///
+/// # Arguments
+///
+/// * `index` - The index of the data pointer, a negative index wraps to a huge one.
+pub(super) extern "sysv64" fn out_of_bounds(index: usize) -> *mut std::io::Error {
+    Box::into_raw(Box::new(std::io::Error::other(OutOfBounds(index as isize))))
+}
@@ -1,33 +1,177 @@
+use std::fmt::{Display, Formatter};
 use std::process::exit;
 
 #[derive(Debug)]
 pub enum Error {
+    /// Error indicating that the source code is not a valid Brainfuck program.
     ParseError(ParserError),
+    /// Error indicating that the program could not read from its input or write to its output.
     RuntimeError(std::io::Error),
-    #[cfg(target_arch="x86_64")]
-    CompileError(std::io::Error)
+    /// Error indicating that the program could not be compiled to native code, because the
+    /// assembler could not be created, the machine code could not be made executable or the
+    /// compiled code could not be stored.
+    #[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+    CompileError(std::io::Error),
+    /// Error indicating that the program executed more steps than allowed.
+    StepLimitExceeded,
+    /// Error indicating that the program ran for longer than allowed.
+    Timeout,
+    /// Error indicating that the shared fuel budget was exhausted.
+    OutOfFuel,
+    /// Error indicating that the data pointer moved outside of the memory array.
+    PointerOutOfBounds { index: isize },
+    /// Error indicating that a memory cell overflowed while the overflow mode is
+    /// `CellOverflow::Error`.
+    CellOverflow { index: usize },
+    /// Error indicating that the execution was stopped through its stop flag.
+    Interrupted,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ParseError(err) => write!(f, "{err}"),
+            Error::RuntimeError(err) => write!(f, "Cannot read or write while running the script: {err}"),
+            #[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+            Error::CompileError(err) => write!(f, "Cannot compile the script: {err}"),
+            Error::StepLimitExceeded => write!(f, "The step limit was exceeded"),
+            Error::Timeout => write!(f, "The time limit was exceeded"),
+            Error::OutOfFuel => write!(f, "The fuel budget was exhausted"),
+            Error::PointerOutOfBounds { index } => {
+                write!(f, "The data pointer moved out of the memory array (index {index})")
+            }
+            Error::CellOverflow { index } => write!(f, "The memory cell {index} overflowed"),
+            Error::Interrupted => write!(f, "The execution was interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ParseError(err) => Some(err),
+            Error::RuntimeError(err) => Some(err),
+            #[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+            Error::CompileError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParserError> for Error {
+    fn from(err: ParserError) -> Self {
+        Error::ParseError(err)
+    }
+}
+
+/// An I/O error is a failed read or write of the running program, a `CompileError` is always
+/// built explicitly.
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::RuntimeError(err)
+    }
 }
 
 /// Enum representing possible errors that can occur during parsing.
-#[derive(Debug)]
+///
+/// Every variant carries the location in the source of the offending character: its byte
+/// offset, and its line and column, both starting at 1, with columns counted in characters.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ParserError {
-    /// Error indicating that a loop was not closed properly.
-    IncompleteLoop,
-    /// Error indicating that an unexpected token was encountered.
-    UnexpectedToken,
+    /// Error indicating that a loop was not closed properly. The location is the one of the
+    /// unmatched '['.
+    IncompleteLoop { position: usize, line: usize, col: usize },
+    /// Error indicating that an unexpected token was encountered. The location is the one of
+    /// the unmatched ']'.
+    UnexpectedToken { position: usize, line: usize, col: usize },
 }
 
 impl ParserError {
+    /// Function to get the byte offset in the source where the error was found.
+    pub fn position(&self) -> usize {
+        match self {
+            ParserError::IncompleteLoop { position, .. } | ParserError::UnexpectedToken { position, .. } => *position,
+        }
+    }
+
+    /// Function to get the line and column in the source where the error was found.
+    pub fn line_col(&self) -> (usize, usize) {
+        match self {
+            ParserError::IncompleteLoop { line, col, .. } | ParserError::UnexpectedToken { line, col, .. } => (*line, *col),
+        }
+    }
+
+    /// Function to show the line of the source where the error was found, with a caret
+    /// pointing at the offending character on the line below.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code in which the error was found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headache::parser::parse;
+    ///
+    /// let err = parse("+++\n>>]").unwrap_err();
+    ///
+    /// assert_eq!(err.caret(&"+++\n>>]"), ">>]\n  ^");
+    /// ```
+    pub fn caret(&self, source: &str) -> String {
+        let (line, col) = self.line_col();
+        let text = source.lines().nth(line - 1).unwrap_or_default();
+        format!("{text}\n{}^", " ".repeat(col - 1))
+    }
+
     /// Function to handle parser errors and exit the program.
     pub fn fail(self) -> ! {
+        eprintln!("{self}");
+        exit(1)
+    }
+}
+
+impl Display for ParserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            ParserError::IncompleteLoop => {
-                eprintln!("All the '[' instructions must be closed with a ']' instruction");
+            ParserError::IncompleteLoop { line, col, .. } => {
+                write!(f, "All the '[' instructions must be closed with a ']' instruction (line {line}, column {col})")
             }
-            ParserError::UnexpectedToken => {
-                eprintln!("Cannot close ']' without first open '[' it");
+            ParserError::UnexpectedToken { line, col, .. } => {
+                write!(f, "Cannot close ']' without first open '[' it (line {line}, column {col})")
             }
         }
-        exit(1)
     }
-}
\ No newline at end of file
+}
+
+impl std::error::Error for ParserError {}
+
+/// Enum representing possible errors that can occur while decoding bytecode.
+///
+/// Every variant carries the offset in the bytecode of the offending byte.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BytecodeError {
+    /// Error indicating that the bytecode ended in the middle of an instruction or a loop.
+    UnexpectedEnd { position: usize },
+    /// Error indicating that a byte is not the opcode of any instruction.
+    InvalidOpcode { opcode: u8, position: usize },
+    /// Error indicating that an operand does not fit in its type.
+    Overflow { position: usize },
+}
+
+impl Display for BytecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BytecodeError::UnexpectedEnd { position } => {
+                write!(f, "The bytecode ended in the middle of an instruction (position {position})")
+            }
+            BytecodeError::InvalidOpcode { opcode, position } => {
+                write!(f, "The byte {opcode:#04x} is not a valid opcode (position {position})")
+            }
+            BytecodeError::Overflow { position } => {
+                write!(f, "The operand does not fit in its type (position {position})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BytecodeError {}
\ No newline at end of file
@@ -1,23 +1,386 @@
-use std::io::{Read, stdin, Stdin, stdout, Stdout, Write};
+use std::io::{ErrorKind, Read, stdin, Stdin, stdout, Stdout, Write};
+use std::marker::PhantomData;
 use std::num::Wrapping;
-use crate::error::Error;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::bytecode::{lower, Op};
+use crate::cell::WrappingCell;
+use crate::error::{Error, ParserError};
 use crate::error::Error::RuntimeError;
-use crate::instruction::Instruction;
-use crate::parser::parse;
+use crate::instruction::{drop_instructions, Instruction};
+use crate::parser::{parse_with_options, ParseOptions};
+use crate::program::Program;
 use crate::MEMORY_SIZE;
 
+#[cfg(feature = "tokio")]
+mod asynchronous;
+#[cfg(feature = "tokio")]
+pub use asynchronous::AsyncExecutor;
+
+/// Number of cells shown on each side of the data pointer by `Executor::debug_dump`.
+const DEBUG_DUMP_RADIUS: usize = 8;
+
+/// Number of steps executed between two checks of the timeout deadline and the stop flag.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Enum representing how a call to `Executor::try_run` finished.
+#[derive(Debug)]
+pub enum RunOutcome {
+    /// The program ran to the end.
+    Completed,
+    /// The program was stopped after executing the maximum number of steps.
+    StepLimit,
+    /// The program was stopped because it ran for longer than the executor timeout.
+    Timeout,
+    /// The program was stopped by any other error.
+    Error(Error),
+}
+
+/// Enum representing a single step of a program executed with `Executor::step`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Step {
+    /// An instruction other than a loop was executed.
+    Instruction(Instruction),
+    /// The start of a loop was reached, `entered` tells whether its body is going to run.
+    LoopStart { entered: bool },
+    /// The end of a loop was reached, `repeated` tells whether its body is going to run again.
+    LoopEnd { repeated: bool },
+}
+
+/// Enum representing the result of a call to `Executor::step`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum StepOutcome {
+    /// A step was executed, `finished` tells whether it was the last step of the program.
+    Executed { step: Step, finished: bool },
+    /// The program had already finished, nothing was executed.
+    Finished,
+}
+
+/// Struct representing how many times each kind of instruction was executed by a run.
+///
+/// It is returned by `Executor::execute_profiled`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Profile {
+    /// Number of executed `Move` instructions.
+    pub moves: u64,
+    /// Number of executed `Add` instructions.
+    pub adds: u64,
+    /// Number of executed `AddAt` instructions.
+    pub add_ats: u64,
+    /// Number of executed `Write` instructions.
+    pub writes: u64,
+    /// Number of executed `Read` instructions.
+    pub reads: u64,
+    /// Number of executed `Loop` instructions.
+    pub loops: u64,
+    /// Number of iterations of the bodies of all the executed loops.
+    pub loop_iterations: u64,
+    /// Number of executed `Clear` instructions.
+    pub clears: u64,
+    /// Number of executed `Set` instructions.
+    pub sets: u64,
+    /// Number of executed `AddTo` instructions.
+    pub add_tos: u64,
+    /// Number of executed `ClearScan` instructions.
+    pub clear_scans: u64,
+    /// Number of executed `MultiplyInto` instructions.
+    pub multiply_intos: u64,
+    /// Number of executed `Seek` instructions.
+    pub seeks: u64,
+    /// Number of executed `DebugDump` instructions.
+    pub debug_dumps: u64,
+    /// Number of executed `WriteConst` instructions.
+    pub write_consts: u64,
+}
+
+impl Profile {
+    /// Function to count an executed instruction.
+    fn record(&mut self, instruction: &Instruction) {
+        let counter = match instruction {
+            Instruction::Move(_) => &mut self.moves,
+            Instruction::Add(_) => &mut self.adds,
+            Instruction::AddAt { .. } => &mut self.add_ats,
+            Instruction::Write => &mut self.writes,
+            Instruction::Read => &mut self.reads,
+            Instruction::Loop(_) => &mut self.loops,
+            Instruction::Clear => &mut self.clears,
+            Instruction::Set(_) => &mut self.sets,
+            Instruction::AddTo { .. } => &mut self.add_tos,
+            Instruction::ClearScan { .. } => &mut self.clear_scans,
+            Instruction::MultiplyInto { .. } => &mut self.multiply_intos,
+            Instruction::Seek { .. } => &mut self.seeks,
+            Instruction::DebugDump => &mut self.debug_dumps,
+            Instruction::WriteConst(_) => &mut self.write_consts,
+        };
+        *counter += 1;
+    }
+}
+
+/// Struct representing a budget of steps that can be shared across several runs.
+///
+/// Every call to `Executor::execute_with_fuel` consumes the steps it executes from the same
+/// budget, so a whole session can be limited to a total number of steps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Fuel {
+    /// Number of steps left in the budget.
+    remaining: u64,
+}
+
+impl Fuel {
+    /// Function to create a new budget of `steps` steps.
+    pub fn new(steps: u64) -> Self {
+        Self { remaining: steps }
+    }
+
+    /// Function to get the number of steps left in the budget.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+/// Enum representing how the memory array behaves when the data pointer moves past its ends.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum TapeMode {
+    /// The memory array has a fixed size and the data pointer wraps around its ends.
+    #[default]
+    Wrapping,
+    /// The memory array grows to the right on demand, zero-filling the new cells. Moving the
+    /// data pointer to the left of the first cell returns a `PointerOutOfBounds` error.
+    Growable,
+    /// The memory array has a fixed size and moving the data pointer past either of its ends
+    /// returns a `PointerOutOfBounds` error, which helps to find programs that leave the tape
+    /// by mistake.
+    Strict,
+}
+
+/// Enum representing what the `Read` instruction does when the input stream is exhausted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum EofBehavior {
+    /// The current memory cell keeps its value.
+    Unchanged,
+    /// The current memory cell is set to 0.
+    Zero,
+    /// The current memory cell is set to -1 (255).
+    NegativeOne,
+    /// The execution stops with a `RuntimeError`.
+    #[default]
+    Error,
+}
+
+/// Enum representing what the arithmetic instructions do when a memory cell overflows.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum CellOverflow {
+    /// The memory cell wraps around, as in standard Brainfuck.
+    #[default]
+    Wrap,
+    /// The execution stops with a `CellOverflow` error carrying the index of the memory cell.
+    ///
+    /// `Add`, `AddTo` and `MultiplyInto` are checked, going below 0 is an overflow too. Setting
+    /// a cell to 0, as `Clear` does and as `AddTo` and `MultiplyInto` do with their origin, is
+    /// never an overflow even if the loop it replaces would have wrapped around.
+    Error,
+}
+
+/// Struct representing the configuration of an `Executor` before it is created.
+///
+/// Every option starts with the same default as `Executor::new`, so only the options that
+/// differ need to be given.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use headache::executor::{EofBehavior, ExecutorBuilder};
+///
+/// let mut executor = ExecutorBuilder::new()
+///     .tape_cells(65536)
+///     .eof(EofBehavior::Zero)
+///     .max_steps(Some(1_000_000))
+///     .build(Cursor::new(b""), Vec::new());
+///
+/// executor.execute("+,").unwrap();
+///
+/// assert_eq!(executor.memory.len(), 65536);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutorBuilder<Cell: WrappingCell = u8> {
+    /// Number of cells of the memory array.
+    tape_cells: usize,
+    /// How the memory array behaves when the data pointer moves past its ends.
+    tape_mode: TapeMode,
+    /// What the `Read` instruction does when the input stream is exhausted.
+    eof_behavior: EofBehavior,
+    /// What the arithmetic instructions do when a memory cell overflows.
+    cell_overflow: CellOverflow,
+    /// Whether the output stream is flushed after every `Write` instruction.
+    flush_per_write: bool,
+    /// Maximum number of steps a run is allowed to execute.
+    max_steps: Option<u64>,
+    /// Maximum wall-clock time a run is allowed to take.
+    timeout: Option<Duration>,
+    /// Options of the parser used by `execute` and `load`.
+    parse_options: ParseOptions,
+    /// Type of the memory cells.
+    cell: PhantomData<Cell>,
+}
+
+impl ExecutorBuilder {
+    /// Function to create a new `ExecutorBuilder` with the default configuration and `u8` cells.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Cell: WrappingCell> Default for ExecutorBuilder<Cell> {
+    fn default() -> Self {
+        Self {
+            tape_cells: MEMORY_SIZE,
+            tape_mode: TapeMode::Wrapping,
+            eof_behavior: EofBehavior::Error,
+            cell_overflow: CellOverflow::Wrap,
+            flush_per_write: false,
+            max_steps: None,
+            timeout: None,
+            parse_options: ParseOptions::default(),
+            cell: PhantomData,
+        }
+    }
+}
+
+impl<Cell: WrappingCell> ExecutorBuilder<Cell> {
+    /// Function to set the number of cells of the memory array, `MEMORY_SIZE` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `cells` - The number of cells of the memory array, at least 1.
+    pub fn tape_cells(self, cells: usize) -> Self {
+        Self { tape_cells: cells, ..self }
+    }
+
+    /// Function to set how the memory array behaves when the data pointer moves past its ends,
+    /// see `TapeMode`.
+    pub fn tape_mode(self, tape_mode: TapeMode) -> Self {
+        Self { tape_mode, ..self }
+    }
+
+    /// Function to set what the `Read` instruction does when the input stream is exhausted,
+    /// see `Executor::set_eof_behavior`.
+    pub fn eof(self, eof_behavior: EofBehavior) -> Self {
+        Self { eof_behavior, ..self }
+    }
+
+    /// Function to set what the arithmetic instructions do when a memory cell overflows, see
+    /// `Executor::set_cell_overflow`.
+    pub fn cell_overflow(self, cell_overflow: CellOverflow) -> Self {
+        Self { cell_overflow, ..self }
+    }
+
+    /// Function to set whether the output stream is flushed after every `Write` instruction,
+    /// see `Executor::set_flush_per_write`.
+    pub fn flush_per_write(self, flush_per_write: bool) -> Self {
+        Self { flush_per_write, ..self }
+    }
+
+    /// Function to set the maximum number of steps a run is allowed to execute, see
+    /// `Executor::set_max_steps`.
+    pub fn max_steps(self, max_steps: Option<u64>) -> Self {
+        Self { max_steps, ..self }
+    }
+
+    /// Function to set the maximum wall-clock time a run is allowed to take, see
+    /// `Executor::set_timeout`.
+    pub fn timeout(self, timeout: Option<Duration>) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Function to set the options of the parser used by `execute` and `load`, see
+    /// `Executor::set_parse_options`.
+    pub fn parse_options(self, parse_options: ParseOptions) -> Self {
+        Self { parse_options, ..self }
+    }
+
+    /// Function to change the type of the memory cells, `u8` by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::ExecutorBuilder;
+    ///
+    /// let executor = ExecutorBuilder::new().cell_type::<u16>().build(Cursor::new(b""), Vec::new());
+    ///
+    /// assert_eq!(executor.memory[0].0, 0u16);
+    /// ```
+    pub fn cell_type<Other: WrappingCell>(self) -> ExecutorBuilder<Other> {
+        ExecutorBuilder {
+            tape_cells: self.tape_cells,
+            tape_mode: self.tape_mode,
+            eof_behavior: self.eof_behavior,
+            cell_overflow: self.cell_overflow,
+            flush_per_write: self.flush_per_write,
+            max_steps: self.max_steps,
+            timeout: self.timeout,
+            parse_options: self.parse_options,
+            cell: PhantomData,
+        }
+    }
+
+    /// Function to create an `Executor` with this configuration, a zeroed memory array and
+    /// index set to 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - An input stream to be used for reading data into the Brainfuck program.
+    /// * `output` - An output stream to be used for writing data from the Brainfuck program.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of cells of the memory array is 0.
+    pub fn build<Input: Read, Output: Write>(self, input: Input, output: Output) -> Executor<Input, Output, Cell> {
+        assert!(self.tape_cells > 0, "the memory array needs at least one cell");
+        Executor {
+            memory: vec![Wrapping(Cell::ZERO); self.tape_cells],
+            index: 0,
+            input,
+            output,
+            tape_mode: self.tape_mode,
+            eof_behavior: self.eof_behavior,
+            cell_overflow: self.cell_overflow,
+            flush_per_write: self.flush_per_write,
+            steps: 0,
+            max_steps: self.max_steps,
+            timeout: self.timeout,
+            deadline: None,
+            stop_flag: None,
+            parse_options: self.parse_options,
+            fuel: None,
+            last_parse_error: None,
+            failed_instruction: None,
+            profile: None,
+            accesses: None,
+            program: vec![],
+            pc: 0,
+        }
+    }
+}
+
 /// Struct representing the state of a Brainfuck program.
 ///
 /// The `Executor` struct contains the memory array used by the Brainfuck program,
 /// as well as the current position of the data pointer in the memory array. It also
 /// contains input and output streams for reading and writing data.
-pub struct Executor<Input: Read, Output: Write> {
+///
+/// The memory cells are `u8` by default, `u16` and `u32` cells can be selected with the
+/// `Cell` type parameter.
+pub struct Executor<Input: Read, Output: Write, Cell: WrappingCell = u8> {
     /// Array representing the memory used by the Brainfuck program.
     ///
-    /// This is an array of `Wrapping<u8>` values, where each value represents a single
-    /// memory cell in the Brainfuck program. The size of the array is determined by the
-    /// `MEMORY_SIZE` constant.
-    pub memory: [Wrapping<u8>; MEMORY_SIZE],
+    /// This is a vector of `Wrapping<Cell>` values, where each value represents a single
+    /// memory cell in the Brainfuck program. Its length is `MEMORY_SIZE` unless a different
+    /// capacity is given with `Executor::with_capacity`, and it grows when the tape mode is
+    /// `TapeMode::Growable`.
+    pub memory: Vec<Wrapping<Cell>>,
     /// Index representing the current position of the data pointer in the memory array.
     ///
     /// This value is used to keep track of which memory cell is currently being accessed
@@ -33,6 +396,40 @@ pub struct Executor<Input: Read, Output: Write> {
     /// This can be any type that implements the `Write` trait. If no output stream is provided
     /// when creating a new `Executor`, `stdout` is used by default.
     output: Output,
+    /// How the memory array behaves when the data pointer moves past its ends.
+    tape_mode: TapeMode,
+    /// What the `Read` instruction does when the input stream is exhausted.
+    eof_behavior: EofBehavior,
+    /// What the arithmetic instructions do when a memory cell overflows.
+    cell_overflow: CellOverflow,
+    /// Whether the output stream is flushed after every `Write` instruction.
+    flush_per_write: bool,
+    /// Number of steps executed since the current run started.
+    steps: u64,
+    /// Maximum number of steps the current run is allowed to execute.
+    max_steps: Option<u64>,
+    /// Maximum wall-clock time a run is allowed to take.
+    timeout: Option<Duration>,
+    /// Instant at which the current run times out.
+    deadline: Option<Instant>,
+    /// Flag that stops the current run when it is set, see `Executor::set_stop_flag`.
+    stop_flag: Option<Arc<AtomicBool>>,
+    /// Options of the parser used by `execute` and `load`.
+    parse_options: ParseOptions,
+    /// Steps left in the fuel budget of the current run, if it has one.
+    fuel: Option<u64>,
+    /// Error found while parsing the code given to the last call to `execute`, if any.
+    last_parse_error: Option<ParserError>,
+    /// Instruction whose execution failed in the current run, if any.
+    failed_instruction: Option<Instruction>,
+    /// Instruction counters of the current run, if it is being profiled.
+    profile: Option<Profile>,
+    /// Access counters of every cell in the current run, if it is being traced.
+    accesses: Option<Vec<u64>>,
+    /// Flat form of the program loaded with `load`, executed one step at a time by `step`.
+    program: Vec<Op>,
+    /// Index in `program` of the next step to execute.
+    pc: usize,
 }
 
 impl <Input: Read, Output: Write> Executor<Input, Output> {
@@ -43,20 +440,277 @@ impl <Input: Read, Output: Write> Executor<Input, Output> {
     /// * `input` - An input stream to be used for reading data into the Brainfuck program.
     /// * `output` - An output stream to be used for writing data from the Brainfuck program.
     pub fn new(input: Input, output: Output) -> Self {
-        Self {
-            memory: [Wrapping(0u8); MEMORY_SIZE],
-            index: 0,
-            input,
-            output,
+        ExecutorBuilder::new().build(input, output)
+    }
+
+    /// Function to create a new State with a memory array of `cells` cells and index set to 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - An input stream to be used for reading data into the Brainfuck program.
+    /// * `output` - An output stream to be used for writing data from the Brainfuck program.
+    /// * `cells` - The number of cells of the memory array.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `cells` is 0.
+    pub fn with_capacity(input: Input, output: Output, cells: usize) -> Self {
+        Self::with_cells(input, output, cells)
+    }
+
+    /// Function to create a new State with the given tape mode and index set to 0.
+    ///
+    /// The memory array starts with `MEMORY_SIZE` cells.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - An input stream to be used for reading data into the Brainfuck program.
+    /// * `output` - An output stream to be used for writing data from the Brainfuck program.
+    /// * `tape_mode` - How the memory array behaves when the data pointer moves past its ends.
+    pub fn with_tape_mode(input: Input, output: Output, tape_mode: TapeMode) -> Self {
+        ExecutorBuilder::new().tape_mode(tape_mode).build(input, output)
+    }
+}
+
+impl <Input: Read, Output: Write, Cell: WrappingCell> Executor<Input, Output, Cell> {
+    /// Function to create a new State with a memory array of `cells` cells of type `Cell` and
+    /// index set to 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - An input stream to be used for reading data into the Brainfuck program.
+    /// * `output` - An output stream to be used for writing data from the Brainfuck program.
+    /// * `cells` - The number of cells of the memory array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    /// use headache::MEMORY_SIZE;
+    ///
+    /// let mut executor = Executor::<_, _, u16>::with_cells(Cursor::new(b""), Vec::new(), MEMORY_SIZE);
+    ///
+    /// executor.execute("++++++++++[>++++++++++++++++++++++++++++++<-]").unwrap();
+    ///
+    /// assert_eq!(executor.memory[1].0, 300);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `cells` is 0.
+    pub fn with_cells(input: Input, output: Output, cells: usize) -> Self {
+        ExecutorBuilder::default().tape_cells(cells).build(input, output)
+    }
+
+    /// Function to set what the `Read` instruction does when the input stream is exhausted.
+    ///
+    /// The default is `EofBehavior::Error`.
+    ///
+    /// # Arguments
+    ///
+    /// * `eof_behavior` - The behavior on end of input.
+    pub fn set_eof_behavior(&mut self, eof_behavior: EofBehavior) {
+        self.eof_behavior = eof_behavior;
+    }
+
+    /// Function to set what the arithmetic instructions do when a memory cell overflows.
+    ///
+    /// The default is `CellOverflow::Wrap`, which matches standard Brainfuck. `CellOverflow::Error`
+    /// is useful to debug programs where an overflow means a bug.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_overflow` - The behavior on overflow.
+    pub fn set_cell_overflow(&mut self, cell_overflow: CellOverflow) {
+        self.cell_overflow = cell_overflow;
+    }
+
+    /// Function to set whether the output stream is flushed after every `Write` instruction.
+    ///
+    /// By default the output stream is only flushed before reading from the input stream and
+    /// when a run finishes, so buffered output streams are not defeated. Flushing after every
+    /// write is useful for interactive sessions where output must show up immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `flush_per_write` - Whether to flush after every write.
+    pub fn set_flush_per_write(&mut self, flush_per_write: bool) {
+        self.flush_per_write = flush_per_write;
+    }
+
+    /// Function to set the options of the parser used by `execute` and `load`.
+    ///
+    /// The default options apply every optimization and treat `#` as a comment. Lowering the
+    /// optimization level helps to find out whether an optimization changes what a program does.
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_options` - The options of the parser, see `ParseOptions`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    /// use headache::parser::{OptLevel, ParseOptions};
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    /// executor.set_parse_options(ParseOptions { opt_level: OptLevel::None, ..ParseOptions::default() });
+    /// let profile = executor.execute_profiled("+++[-]").unwrap();
+    ///
+    /// assert_eq!(profile.adds, 6);
+    /// assert_eq!(profile.clears, 0);
+    /// ```
+    pub fn set_parse_options(&mut self, parse_options: ParseOptions) {
+        self.parse_options = parse_options;
+    }
+
+    /// Function to zero the memory array and move the data pointer back to the first cell.
+    ///
+    /// The input and output streams and the configuration of the executor are kept, so the
+    /// same executor can run a new program from a clean state.
+    pub fn reset(&mut self) {
+        self.memory.fill(Wrapping(Cell::ZERO));
+        self.index = 0;
+    }
+
+    /// Function to get the memory array used by the Brainfuck program.
+    pub fn memory(&self) -> &[Wrapping<Cell>] {
+        &self.memory
+    }
+
+    /// Function to get the current position of the data pointer in the memory array.
+    pub fn pointer(&self) -> usize {
+        self.index
+    }
+
+    /// Function to copy `cells` into the memory array starting at the index `start`.
+    ///
+    /// This seeds the memory before running a program that expects data already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the memory cell receiving the first value.
+    /// * `cells` - The values to copy.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a `PointerOutOfBounds` error with the first index outside of the
+    /// memory array if the values do not fit in it, the memory array is left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    ///
+    /// let mut output = Vec::new();
+    /// let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    ///
+    /// executor.set_cells(0, &[72, 73]).unwrap();
+    /// executor.execute(".>.").unwrap();
+    ///
+    /// assert_eq!(&output, b"HI");
+    /// ```
+    pub fn set_cells(&mut self, start: usize, cells: &[Cell]) -> Result<(), Error> {
+        let size = self.memory.len();
+        if start.saturating_add(cells.len()) > size {
+            return Err(Error::PointerOutOfBounds { index: start.max(size) as isize });
         }
+        for (cell, &value) in self.memory[start..].iter_mut().zip(cells) {
+            *cell = Wrapping(value);
+        }
+        Ok(())
+    }
+
+    /// Function to describe the data pointer and the memory cells around it in a single line.
+    ///
+    /// Up to 8 cells are shown on each side of the data pointer as `index:value`, with the
+    /// current cell between brackets. This is what the `DebugDump` instruction prints to stderr.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    ///
+    /// executor.execute("++>+++").unwrap();
+    ///
+    /// assert!(executor.debug_dump().starts_with("pointer 1: 0:2 [1:3] 2:0 "));
+    /// ```
+    pub fn debug_dump(&self) -> String {
+        let start = self.index.saturating_sub(DEBUG_DUMP_RADIUS);
+        let end = (self.index + DEBUG_DUMP_RADIUS).min(self.memory.len() - 1);
+        let cells: Vec<String> = (start..=end)
+            .map(|index| match self.memory[index].0 {
+                value if index == self.index => format!("[{index}:{value:?}]"),
+                value => format!("{index}:{value:?}"),
+            })
+            .collect();
+        format!("pointer {}: {}", self.index, cells.join(" "))
+    }
+
+    /// Function to get the index and value of every non-zero memory cell, in index order.
+    ///
+    /// This is a compact snapshot of the memory array, useful for debugging and assertions.
+    pub fn dump_nonzero(&self) -> Vec<(usize, Cell)> {
+        self.memory
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.0 != Cell::ZERO)
+            .map(|(index, cell)| (index, cell.0))
+            .collect()
+    }
+
+    /// Function to set the maximum number of steps a run is allowed to execute.
+    ///
+    /// Every primitive instruction and every loop iteration counts as one step, so even an
+    /// empty infinite loop such as `+[]` exhausts the budget. When the budget is exhausted
+    /// the run is stopped with a `StepLimitExceeded` error. `None` disables the limit, which
+    /// is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_steps` - The maximum number of steps of a run.
+    pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+        self.max_steps = max_steps;
+    }
+
+    /// Function to set the maximum wall-clock time a run is allowed to take.
+    ///
+    /// When the timeout expires the run is stopped with a `Timeout` error. `None` disables
+    /// the timeout, which is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration of a run.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Function to set a flag that stops the running program when it is set.
+    ///
+    /// The flag is checked every few steps, so it can be set from another thread or from a
+    /// signal handler to stop a program that runs for too long, with an `Interrupted` error.
+    /// The flag is never cleared by the executor, it must be cleared before running again.
+    /// `None` disables the check, which is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_flag` - The flag stopping the program.
+    pub fn set_stop_flag(&mut self, stop_flag: Option<Arc<AtomicBool>>) {
+        self.stop_flag = stop_flag;
     }
 
     /// Function to execute a string of Brainfuck code.
     ///
     /// This function takes a string containing Brainfuck code and executes it. The code is first
-    /// parsed into a vector of `Instruction` values using the `parse` function from the `parser`
-    /// module. The resulting instructions are then executed in order using the `_execute`
-    /// function.
+    /// parsed into a vector of `Instruction` values using the `parse_with_options` function from
+    /// the `parser` module, with the options given to `set_parse_options`. The resulting
+    /// instructions are then executed in order using the `_execute` function.
     ///
     /// # Arguments
     ///
@@ -85,13 +739,343 @@ impl <Input: Read, Output: Write> Executor<Input, Output> {
     /// * An I/O error occurs while reading from the input stream or writing to the output stream.
     ///   In this case, a `RuntimeError` is returned containing the underlying I/O error.
     pub fn execute(&mut self, code: &str) -> Result<(), Error> {
-        let instructions = match parse(code) {
-            Ok(instructions) => instructions,
-            Err(err) => {
-                return Err(Error::ParseError(err))
+        let instructions = self.parse_code(code)?;
+        let result = self.execute_instructions(&instructions);
+        drop_instructions(instructions);
+        result
+    }
+
+    /// Function to parse the code given to `execute`, remembering the error for
+    /// `last_parse_error`.
+    fn parse_code(&mut self, code: &str) -> Result<Vec<Instruction>, Error> {
+        let parsed = parse_with_options(code, self.parse_options);
+        self.last_parse_error = parsed.as_ref().err().cloned();
+        Ok(parsed?)
+    }
+
+    /// Function to execute a vector of already parsed Instructions.
+    ///
+    /// This function runs the instructions like `execute` does with the instructions parsed
+    /// from its code, so a program parsed once, transformed or loaded with
+    /// `parser::from_bytecode` can be run without parsing it again.
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - A slice of Instructions to be executed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    /// use headache::parser::{from_bytecode, parse, to_bytecode};
+    ///
+    /// let bytecode = to_bytecode(&parse("++++++++[>++++++++<-]>+.").unwrap());
+    /// let mut output = Vec::new();
+    /// let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    ///
+    /// executor.execute_instructions(&from_bytecode(&bytecode).unwrap()).unwrap();
+    ///
+    /// assert_eq!(&output, b"A");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `execute`, except for `ParseError`.
+    pub fn execute_instructions(&mut self, instructions: &[Instruction]) -> Result<(), Error> {
+        self.start_run();
+        let result = self._execute(instructions);
+        self.finish_run(result)
+    }
+
+    /// Function to execute a string of Brainfuck code consuming steps from a shared budget.
+    ///
+    /// Every primitive instruction and every loop iteration consumes one step of `fuel`, which
+    /// keeps the steps it did not consume when the function returns, so it can be passed to
+    /// the next call.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A string containing Brainfuck code to be executed.
+    /// * `fuel` - The budget of steps the code is allowed to consume.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `execute`, plus an `OutOfFuel` error if the
+    /// budget is exhausted before the code finishes.
+    pub fn execute_with_fuel(&mut self, code: &str, fuel: &mut Fuel) -> Result<(), Error> {
+        self.fuel = Some(fuel.remaining);
+        let result = self.execute(code);
+        if let Some(remaining) = self.fuel.take() {
+            fuel.remaining = remaining;
+        }
+        result
+    }
+
+    /// Function to execute a string of Brainfuck code counting the executed instructions.
+    ///
+    /// Profiling has a small cost, so it only happens in the runs started by this function.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A string containing Brainfuck code to be executed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    /// let profile = executor.execute_profiled("+++[>+<-.]").unwrap();
+    ///
+    /// assert_eq!(profile.loop_iterations, 3);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `execute`.
+    pub fn execute_profiled(&mut self, code: &str) -> Result<Profile, Error> {
+        self.profile = Some(Profile::default());
+        let result = self.execute(code);
+        let profile = self.profile.take().unwrap_or_default();
+        result.map(|_| profile)
+    }
+
+    /// Function to execute a string of Brainfuck code counting the accesses to every cell.
+    ///
+    /// Unlike `execute_profiled`, which counts the executed instructions, this counts how many
+    /// instructions read or write every cell, to find the hot spots of the memory array. Testing
+    /// the current cell at the start or the end of a loop is an access, moving the data pointer
+    /// is not. Tracing has a small cost, so it only happens in the runs started by this function.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A string containing Brainfuck code to be executed.
+    ///
+    /// # Returns
+    ///
+    /// * The number of accesses to every cell, as long as the memory array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    /// let accesses = executor.execute_traced(">+.>").unwrap();
+    ///
+    /// assert_eq!(&accesses[..3], &[0, 2, 0]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `execute`.
+    pub fn execute_traced(&mut self, code: &str) -> Result<Vec<u64>, Error> {
+        self.accesses = Some(vec![0; self.memory.len()]);
+        let result = self.execute(code);
+        let mut accesses = self.accesses.take().unwrap_or_default();
+        // A growable memory array may have grown past the last accessed cell.
+        accesses.resize(self.memory.len(), 0);
+        result.map(|_| accesses)
+    }
+
+    /// Function to load a string of Brainfuck code to be executed one step at a time.
+    ///
+    /// The code is parsed and flattened so `step` can walk in and out of loops. Loading a
+    /// program does not touch the memory array nor the data pointer.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A string containing Brainfuck code to be executed.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a `ParseError` if the given Brainfuck code cannot be parsed successfully.
+    pub fn load(&mut self, code: &str) -> Result<(), Error> {
+        let instructions = parse_with_options(code, self.parse_options)?;
+        self.program = lower(&instructions);
+        drop_instructions(instructions);
+        self.pc = 0;
+        self.start_run();
+        Ok(())
+    }
+
+    /// Function to execute the next step of the program loaded with `load`.
+    ///
+    /// Every instruction other than a loop is a step, and so are reaching the start and the end
+    /// of a loop, where the executor decides whether to run the loop body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::{Executor, Step, StepOutcome};
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    /// executor.load("[-]>").unwrap();
+    ///
+    /// let outcome = executor.step().unwrap();
+    /// assert!(matches!(outcome, StepOutcome::Executed { step: Step::Instruction(_), finished: false }));
+    /// assert_eq!(executor.pointer(), 0);
+    ///
+    /// executor.step().unwrap();
+    /// assert_eq!(executor.pointer(), 1);
+    /// assert_eq!(executor.step().unwrap(), StepOutcome::Finished);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same runtime errors as `execute`.
+    pub fn step(&mut self) -> Result<StepOutcome, Error> {
+        let Some(op) = self.program.get(self.pc) else {
+            return Ok(StepOutcome::Finished);
+        };
+        let is_zero = self.memory[self.index].0 == Cell::ZERO;
+        let step = match *op {
+            Op::Instruction(ref instruction) => {
+                let instruction = instruction.clone();
+                match self.execute_instruction(&instruction) {
+                    Ok(()) => {
+                        self.pc += 1;
+                        Ok(Step::Instruction(instruction))
+                    }
+                    Err(err) => {
+                        self.failed_instruction = Some(instruction);
+                        Err(err)
+                    }
+                }
             }
+            Op::JumpIfZero(end) => self.tick().map(|_| {
+                self.pc = if is_zero { end + 1 } else { self.pc + 1 };
+                Step::LoopStart { entered: !is_zero }
+            }),
+            Op::JumpIfNonZero(start) => self.tick().map(|_| {
+                self.pc = if is_zero { self.pc + 1 } else { start + 1 };
+                Step::LoopEnd { repeated: !is_zero }
+            }),
         };
-        self._execute(&instructions)
+        // The output is flushed once the program is over, finished or failed, like `execute` does.
+        let finished = self.pc >= self.program.len();
+        let flushed = if step.is_err() || finished { self.output.flush().map_err(RuntimeError) } else { Ok(()) };
+        let step = step?;
+        flushed?;
+        Ok(StepOutcome::Executed { step, finished })
+    }
+
+    /// Function to get the error found while parsing the code given to the last call to
+    /// `execute`.
+    ///
+    /// This lets tools such as editors locate the error without parsing the code again.
+    /// It returns `None` if the last code was parsed successfully.
+    pub fn last_parse_error(&self) -> Option<&ParserError> {
+        self.last_parse_error.as_ref()
+    }
+
+    /// Function to get the instruction that failed in the last run started by `execute`,
+    /// `execute_instructions` or `load`.
+    ///
+    /// This lets tools report where a program stopped with an error. It returns `None` if the
+    /// last run did not fail, or if it failed while entering or repeating a loop, for example
+    /// when the step limit was exceeded there.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::Executor;
+    /// use headache::instruction::Instruction;
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    ///
+    /// assert!(executor.execute("+.,").is_err());
+    /// assert_eq!(executor.failed_instruction(), Some(&Instruction::Read));
+    /// ```
+    pub fn failed_instruction(&self) -> Option<&Instruction> {
+        self.failed_instruction.as_ref()
+    }
+
+    /// Function to run a parsed program with a step budget and categorize how it finished.
+    ///
+    /// Every primitive instruction and every loop iteration counts as one step. The run is
+    /// also stopped if it takes longer than the timeout set with `set_timeout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The program to be executed.
+    /// * `max_steps` - The maximum number of steps the program is allowed to execute.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use headache::executor::{Executor, RunOutcome};
+    /// use headache::program::Program;
+    ///
+    /// let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    /// let program = Program::parse("+[]").unwrap();
+    ///
+    /// assert!(matches!(executor.try_run(&program, 1_000), RunOutcome::StepLimit));
+    /// ```
+    pub fn try_run(&mut self, program: &Program, max_steps: u64) -> RunOutcome {
+        let previous = self.max_steps.replace(max_steps);
+        self.start_run();
+        let result = self._execute(program.instructions());
+        let result = self.finish_run(result);
+        self.max_steps = previous;
+        match result {
+            Ok(()) => RunOutcome::Completed,
+            Err(Error::StepLimitExceeded) => RunOutcome::StepLimit,
+            Err(Error::Timeout) => RunOutcome::Timeout,
+            Err(err) => RunOutcome::Error(err),
+        }
+    }
+
+    /// Function to reset the step counter and the deadline before a new run.
+    fn start_run(&mut self) {
+        self.steps = 0;
+        self.failed_instruction = None;
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    /// Function to flush the output stream once a run is over.
+    ///
+    /// The output is flushed even if the run failed, but the error of the run is the one returned.
+    fn finish_run(&mut self, result: Result<(), Error>) -> Result<(), Error> {
+        let flushed = self.output.flush().map_err(RuntimeError);
+        result.and(flushed)
+    }
+
+    /// Function to account for one executed step.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a `StepLimitExceeded` error if the step budget is exhausted, an
+    /// `OutOfFuel` error if the fuel budget is exhausted, a `Timeout` error if the deadline
+    /// has passed and an `Interrupted` error if the stop flag is set.
+    fn tick(&mut self) -> Result<(), Error> {
+        self.steps += 1;
+        if let Some(fuel) = &mut self.fuel {
+            if *fuel == 0 {
+                return Err(Error::OutOfFuel);
+            }
+            *fuel -= 1;
+        }
+        if let Some(max_steps) = self.max_steps {
+            if self.steps > max_steps {
+                return Err(Error::StepLimitExceeded);
+            }
+        }
+        if self.steps.is_multiple_of(CHECK_INTERVAL) {
+            if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(Error::Timeout);
+            }
+            if self.stop_flag.as_ref().is_some_and(|stop_flag| stop_flag.load(Ordering::Relaxed)) {
+                return Err(Error::Interrupted);
+            }
+        }
+        Ok(())
     }
 
     /// Function to execute a vector of Brainfuck instructions.
@@ -100,13 +1084,15 @@ impl <Input: Read, Output: Write> Executor<Input, Output> {
     /// of each instruction is determined by its variant:
     ///
     /// * `Move(delta)` - Moves the data pointer by `delta` positions. If `delta` is positive, the
-    ///                   data pointer is moved to the right; if it is negative, it is moved to the left.
+    ///   data pointer is moved to the right; if it is negative, it is moved to the left.
     /// * `Add(n)` - Adds `n` to the value of the current memory cell.
     /// * `Write` - Writes the value of the current memory cell to the output stream.
     /// * `Read` - Reads a value from the input stream and stores it in the current memory cell.
     /// * `Loop(instructions)` - Executes a loop. The loop body consists of the given `instructions`,
-    ///                          which are executed repeatedly until the value of the current memory cell
-    ///                          becomes 0.
+    ///   which are executed repeatedly until the value of the current memory cell becomes 0.
+    ///
+    /// The instructions are lowered to their flat form first, where loops are jumps, and run by a
+    /// single dispatch loop, so nested loops do not recurse.
     ///
     /// # Arguments
     ///
@@ -116,44 +1102,274 @@ impl <Input: Read, Output: Write> Executor<Input, Output> {
     ///
     /// This function returns an error if any of the following conditions are met:
     ///
-    /// * The given Brainfuck code cannot be parsed successfully. In this case, a `ParseError`
-    ///   is returned containing the specific parsing error that occurred.
     /// * An I/O error occurs while reading from the input stream or writing to the output stream.
     ///   In this case, a `RuntimeError` is returned containing the underlying I/O error.
+    /// * The step budget is exhausted or the deadline has passed. In this case, a
+    ///   `StepLimitExceeded` or `Timeout` error is returned.
     pub fn _execute(&mut self, instructions: &[Instruction]) -> Result<(), Error> {
-        for instruction in instructions {
-            match instruction {
-                Instruction::Move(delta) => {
-                    let delta = (MEMORY_SIZE as isize + delta % MEMORY_SIZE as isize) as usize;
-                    self.index = (self.index + delta) % MEMORY_SIZE;
+        let ops = lower(instructions);
+        let mut pc = 0;
+        while pc < ops.len() {
+            match &ops[pc] {
+                Op::Instruction(instruction) => {
+                    if let Err(err) = self.execute_instruction(instruction) {
+                        self.failed_instruction = Some(instruction.clone());
+                        return Err(err);
+                    }
                 }
-                Instruction::Add(n) => { self.memory[self.index] += *n }
-                Instruction::Write => {
-                    self.output.write_all(&[self.memory[self.index].0]).map_err(RuntimeError)?;
-                    self.output.flush().map_err(RuntimeError)?;
+                jump => {
+                    if let Some(target) = self.execute_jump(jump)? {
+                        pc = target;
+                    }
                 }
-                Instruction::Read => {
-                    let mut buffer = [0u8];
-                    self.input.read_exact(&mut buffer).map_err(RuntimeError)?;
-                    self.memory[self.index] = Wrapping(buffer[0]);
+            }
+            pc += 1;
+        }
+        Ok(())
+    }
+
+    /// Function to execute the start or the end of a loop in the flat form of a program.
+    ///
+    /// # Arguments
+    ///
+    /// * `jump` - The `JumpIfZero` or `JumpIfNonZero` to be executed.
+    ///
+    /// # Returns
+    ///
+    /// The index of the op to jump to, or `None` to continue with the next op.
+    fn execute_jump(&mut self, jump: &Op) -> Result<Option<usize>, Error> {
+        match *jump {
+            Op::JumpIfZero(end) => {
+                // Reaching a loop is a step, and so is every iteration of its body.
+                self.tick()?;
+                if let Some(profile) = &mut self.profile {
+                    profile.loops += 1;
                 }
-                Instruction::Loop(instructions) => {
-                    while self.memory[self.index].0 != 0 {
-                        self._execute(instructions)?;
-                    }
+                self.record_access(self.index);
+                if self.memory[self.index].0 == Cell::ZERO {
+                    return Ok(Some(end));
                 }
-                Instruction::Clear => self.memory[self.index] = Wrapping(0),
-                Instruction::MoveTo { offset } => {
-                    let delta = (MEMORY_SIZE as isize + offset % MEMORY_SIZE as isize) as usize;
-                    let to = (self.index + delta) % MEMORY_SIZE;
+                self.start_iteration()?;
+                Ok(None)
+            }
+            Op::JumpIfNonZero(start) => {
+                self.record_access(self.index);
+                if self.memory[self.index].0 == Cell::ZERO {
+                    return Ok(None);
+                }
+                self.start_iteration()?;
+                Ok(Some(start))
+            }
+            Op::Instruction(_) => unreachable!("only jumps are executed by execute_jump"),
+        }
+    }
+
+    /// Function to account for the execution of an instruction other than a loop.
+    fn count_step(&mut self, instruction: &Instruction) -> Result<(), Error> {
+        self.tick()?;
+        if let Some(profile) = &mut self.profile {
+            profile.record(instruction);
+        }
+        Ok(())
+    }
 
-                    self.memory[to] += self.memory[self.index];
-                    self.memory[self.index] = Wrapping(0);
+    /// Function to account for an access to the cell at `index`, if the run is being traced.
+    fn record_access(&mut self, index: usize) {
+        if let Some(accesses) = &mut self.accesses {
+            if index >= accesses.len() {
+                accesses.resize(self.memory.len(), 0);
+            }
+            accesses[index] += 1;
+        }
+    }
+
+    /// Function to account for a new iteration of a loop body.
+    fn start_iteration(&mut self) -> Result<(), Error> {
+        self.tick()?;
+        if let Some(profile) = &mut self.profile {
+            profile.loop_iterations += 1;
+        }
+        Ok(())
+    }
+
+    /// Function to execute a single Brainfuck instruction other than a loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `instruction` - The instruction to be executed.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `_execute`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `instruction` is a `Loop`, loops are executed by `_execute`.
+    fn execute_instruction(&mut self, instruction: &Instruction) -> Result<(), Error> {
+        self.count_step(instruction)?;
+        if !matches!(instruction, Instruction::Move(_) | Instruction::AddAt { .. } | Instruction::WriteConst(_) | Instruction::DebugDump) {
+            self.record_access(self.index);
+        }
+        match instruction {
+            Instruction::Move(delta) => self.index = self.offset_index(*delta)?,
+            Instruction::Add(n) => self.add_delta(self.index, *n)?,
+            Instruction::AddAt { offset, value } => {
+                let to = self.offset_index(*offset)?;
+                self.record_access(to);
+                self.add_delta(to, *value)?;
+            }
+            Instruction::Write => {
+                let mut buffer = [0u8; 4];
+                let buffer = &mut buffer[..Cell::BYTES];
+                self.memory[self.index].0.write_le_slice(buffer);
+                self.output.write_all(buffer)?;
+                if self.flush_per_write {
+                    self.output.flush()?;
                 }
             }
+            Instruction::WriteConst(constant) => {
+                self.output.write_all(constant)?;
+                if self.flush_per_write {
+                    self.output.flush()?;
+                }
+            }
+            Instruction::Read => {
+                // The output asked for the input must show up before waiting for it.
+                self.output.flush()?;
+                let mut buffer = [0u8; 4];
+                let buffer = &mut buffer[..Cell::BYTES];
+                match self.input.read_exact(buffer) {
+                    Ok(()) => self.memory[self.index] = Wrapping(Cell::from_le_slice(buffer)),
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => self.end_of_input(err)?,
+                    Err(err) => return Err(RuntimeError(err)),
+                }
+            }
+            Instruction::Loop(_) => unreachable!("loops are executed by _execute"),
+            Instruction::Clear => self.memory[self.index] = Wrapping(Cell::ZERO),
+            Instruction::Set(n) => {
+                self.memory[self.index].0 = match self.cell_overflow {
+                    CellOverflow::Wrap => Cell::ZERO.add_delta(*n),
+                    CellOverflow::Error => Cell::ZERO.checked_add_delta(*n).ok_or(Error::CellOverflow { index: self.index })?,
+                };
+            }
+            // A zero origin means the replaced loop never ran, so its target must not be reached.
+            Instruction::AddTo { .. } | Instruction::MultiplyInto { .. } if self.memory[self.index].0 == Cell::ZERO => {}
+            Instruction::AddTo { offset } => {
+                let to = self.offset_index(*offset)?;
+                let value = self.memory[self.index].0;
+
+                self.add_product(to, value, 1)?;
+                self.memory[self.index] = Wrapping(Cell::ZERO);
+            }
+            Instruction::ClearScan { stride } => {
+                while self.memory[self.index].0 != Cell::ZERO {
+                    self.tick()?;
+                    self.memory[self.index] = Wrapping(Cell::ZERO);
+                    self.index = self.offset_index(*stride)?;
+                    self.record_access(self.index);
+                }
+            }
+            Instruction::Seek { stride } => {
+                while self.memory[self.index].0 != Cell::ZERO {
+                    self.tick()?;
+                    self.index = self.offset_index(*stride)?;
+                    self.record_access(self.index);
+                }
+            }
+            Instruction::MultiplyInto { targets } => {
+                let value = self.memory[self.index].0;
+
+                for &(offset, factor) in targets {
+                    let to = self.offset_index(offset)?;
+                    self.add_product(to, value, factor)?;
+                }
+                self.memory[self.index] = Wrapping(Cell::ZERO);
+            }
+            Instruction::DebugDump => {
+                // The output written so far must show up before the dump.
+                self.output.flush()?;
+                eprintln!("{}", self.debug_dump());
+            }
+        }
+        Ok(())
+    }
+
+    /// Function to update the current cell when a `Read` instruction finds the end of the input,
+    /// following the EOF behavior of the executor.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a `RuntimeError` with the given error if the behavior is
+    /// `EofBehavior::Error`.
+    fn end_of_input(&mut self, err: std::io::Error) -> Result<(), Error> {
+        match self.eof_behavior {
+            EofBehavior::Unchanged => {}
+            EofBehavior::Zero => self.memory[self.index] = Wrapping(Cell::ZERO),
+            EofBehavior::NegativeOne => self.memory[self.index] = Wrapping(Cell::NEGATIVE_ONE),
+            EofBehavior::Error => return Err(RuntimeError(err)),
         }
         Ok(())
     }
+
+    /// Function to add an 8-bit two's complement delta to the memory cell at `to`, following the
+    /// overflow mode of the executor.
+    fn add_delta(&mut self, to: usize, delta: u8) -> Result<(), Error> {
+        let cell = self.memory[to].0;
+        self.memory[to].0 = match self.cell_overflow {
+            CellOverflow::Wrap => cell.add_delta(delta),
+            CellOverflow::Error => cell.checked_add_delta(delta).ok_or(Error::CellOverflow { index: to })?,
+        };
+        Ok(())
+    }
+
+    /// Function to add `value` multiplied by `factor` to the memory cell at `to`, following the
+    /// overflow mode of the executor.
+    fn add_product(&mut self, to: usize, value: Cell, factor: u8) -> Result<(), Error> {
+        self.record_access(to);
+        let cell = self.memory[to].0;
+        self.memory[to].0 = match self.cell_overflow {
+            CellOverflow::Wrap => cell.add_cell(value.mul_delta(factor)),
+            CellOverflow::Error => cell.checked_add_product(value, factor).ok_or(Error::CellOverflow { index: to })?,
+        };
+        Ok(())
+    }
+
+    /// Function to compute the index `delta` cells away from the data pointer.
+    ///
+    /// In `Wrapping` mode the index wraps around the memory array boundaries. In `Growable`
+    /// mode the memory array is grown, at least doubling its size, to contain the index.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a `PointerOutOfBounds` error if the index is to the left of the
+    /// first cell in `Growable` mode, or outside of the memory array in `Strict` mode.
+    fn offset_index(&mut self, delta: isize) -> Result<usize, Error> {
+        let size = self.memory.len();
+        match self.tape_mode {
+            TapeMode::Wrapping => {
+                let delta = (size as isize + delta % size as isize) as usize;
+                Ok((self.index + delta) % size)
+            }
+            TapeMode::Growable => {
+                let index = self.index as isize + delta;
+                if index < 0 {
+                    return Err(Error::PointerOutOfBounds { index });
+                }
+                let index = index as usize;
+                if index >= size {
+                    self.memory.resize((index + 1).max(size * 2), Wrapping(Cell::ZERO));
+                }
+                Ok(index)
+            }
+            TapeMode::Strict => {
+                let index = self.index as isize + delta;
+                if index < 0 || index >= size as isize {
+                    return Err(Error::PointerOutOfBounds { index });
+                }
+                Ok(index as usize)
+            }
+        }
+    }
 }
 
 impl Default for Executor<Stdin, Stdout> {
@@ -162,11 +1378,6 @@ impl Default for Executor<Stdin, Stdout> {
     /// This function creates a new `Executor` with `stdin` as the input stream and `stdout`
     /// as the output stream.
     fn default() -> Self {
-        Self {
-            memory: [Wrapping(0u8); MEMORY_SIZE],
-            index: 0,
-            input: stdin(),
-            output: stdout(),
-        }
+        Self::new(stdin(), stdout())
     }
 }
@@ -0,0 +1,186 @@
+use std::io::{empty, sink, Empty, ErrorKind, Sink};
+use std::num::Wrapping;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::bytecode::{lower, Op};
+use crate::error::Error;
+use crate::error::Error::RuntimeError;
+use crate::executor::{Executor, ExecutorBuilder};
+use crate::instruction::Instruction;
+
+/// Struct representing the state of a Brainfuck program whose input and output are Tokio streams.
+///
+/// Every instruction but `Read`, `Write`, `WriteConst` and `DebugDump` is run by an inner
+/// `Executor`, which holds the memory array and the configuration, so both executors behave the
+/// same. The I/O instructions await the streams instead of blocking the runtime. Stretches of
+/// the program that neither read nor write do not yield to the runtime, so long computations
+/// should be bounded with `Executor::set_max_steps` or `Executor::set_timeout` through
+/// `executor_mut`.
+///
+/// The memory cells are `u8`.
+pub struct AsyncExecutor<Input, Output> {
+    /// Executor running the instructions other than I/O, on its own memory array.
+    executor: Executor<Empty, Sink>,
+    /// Input stream used for reading data into the Brainfuck program.
+    input: Input,
+    /// Output stream used for writing data from the Brainfuck program.
+    output: Output,
+}
+
+impl<Input: AsyncRead + Unpin, Output: AsyncWrite + Unpin> AsyncExecutor<Input, Output> {
+    /// Function to create a new `AsyncExecutor` with an initialized memory array and index set to 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - An input stream to be used for reading data into the Brainfuck program.
+    /// * `output` - An output stream to be used for writing data from the Brainfuck program.
+    pub fn new(input: Input, output: Output) -> Self {
+        Self::with_builder(ExecutorBuilder::new(), input, output)
+    }
+
+    /// Function to create a new `AsyncExecutor` whose memory array and configuration are given
+    /// by an `ExecutorBuilder`.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The configuration of the executor.
+    /// * `input` - An input stream to be used for reading data into the Brainfuck program.
+    /// * `output` - An output stream to be used for writing data from the Brainfuck program.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of cells of the memory array is 0.
+    pub fn with_builder(builder: ExecutorBuilder, input: Input, output: Output) -> Self {
+        Self { executor: builder.build(empty(), sink()), input, output }
+    }
+
+    /// Function to get the inner executor, to inspect the memory array and the data pointer.
+    pub fn executor(&self) -> &Executor<Empty, Sink> {
+        &self.executor
+    }
+
+    /// Function to get the inner executor mutably, to change its configuration or its memory.
+    pub fn executor_mut(&mut self) -> &mut Executor<Empty, Sink> {
+        &mut self.executor
+    }
+
+    /// Function to execute a string of Brainfuck code.
+    ///
+    /// The code is parsed with the options of the inner executor, see
+    /// `Executor::set_parse_options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A string containing Brainfuck code to be executed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headache::executor::AsyncExecutor;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut output = Vec::new();
+    /// let mut executor = AsyncExecutor::new(&b"@"[..], &mut output);
+    ///
+    /// executor.execute(",+.").await.unwrap();
+    ///
+    /// assert_eq!(&output, b"A");
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `Executor::execute`.
+    pub async fn execute(&mut self, code: &str) -> Result<(), Error> {
+        let instructions = self.executor.parse_code(code)?;
+        self.execute_instructions(&instructions).await
+    }
+
+    /// Function to execute a vector of already parsed Instructions, see
+    /// `Executor::execute_instructions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - A slice of Instructions to be executed.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as `Executor::execute_instructions`.
+    pub async fn execute_instructions(&mut self, instructions: &[Instruction]) -> Result<(), Error> {
+        self.executor.start_run();
+        let result = self._execute(instructions).await;
+        // The output is flushed even if the run failed, but the error of the run is the one returned.
+        let flushed = self.output.flush().await.map_err(RuntimeError);
+        result.and(flushed)
+    }
+
+    /// Function to execute a vector of Brainfuck instructions in their flat form, like
+    /// `Executor::_execute` does.
+    async fn _execute(&mut self, instructions: &[Instruction]) -> Result<(), Error> {
+        let ops = lower(instructions);
+        let mut pc = 0;
+        while pc < ops.len() {
+            match &ops[pc] {
+                Op::Instruction(instruction) => {
+                    let result = match instruction {
+                        Instruction::Read | Instruction::Write | Instruction::WriteConst(_) | Instruction::DebugDump => {
+                            self.execute_io(instruction).await
+                        }
+                        _ => self.executor.execute_instruction(instruction),
+                    };
+                    if let Err(err) = result {
+                        self.executor.failed_instruction = Some(instruction.clone());
+                        return Err(err);
+                    }
+                }
+                jump => {
+                    if let Some(target) = self.executor.execute_jump(jump)? {
+                        pc = target;
+                    }
+                }
+            }
+            pc += 1;
+        }
+        Ok(())
+    }
+
+    /// Function to execute an instruction that reads from the input stream or writes to the
+    /// output stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `instruction` - The `Read`, `Write`, `WriteConst` or `DebugDump` instruction to be executed.
+    async fn execute_io(&mut self, instruction: &Instruction) -> Result<(), Error> {
+        let executor = &mut self.executor;
+        executor.count_step(instruction)?;
+        match instruction {
+            Instruction::Write | Instruction::WriteConst(_) => {
+                let cell = [executor.memory[executor.index].0];
+                let bytes = match instruction {
+                    Instruction::WriteConst(constant) => constant.as_slice(),
+                    _ => &cell,
+                };
+                self.output.write_all(bytes).await?;
+                if executor.flush_per_write {
+                    self.output.flush().await?;
+                }
+            }
+            Instruction::Read => {
+                // The output asked for the input must show up before waiting for it.
+                self.output.flush().await?;
+                let mut byte = [0u8];
+                match self.input.read_exact(&mut byte).await {
+                    Ok(_) => executor.memory[executor.index] = Wrapping(byte[0]),
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => executor.end_of_input(err)?,
+                    Err(err) => return Err(RuntimeError(err)),
+                }
+            }
+            Instruction::DebugDump => {
+                // The output written so far must show up before the dump.
+                self.output.flush().await?;
+                eprintln!("{}", executor.debug_dump());
+            }
+            _ => unreachable!("only I/O instructions are executed by execute_io"),
+        }
+        Ok(())
+    }
+}
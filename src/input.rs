@@ -0,0 +1,59 @@
+use std::io::Read;
+
+/// Struct representing an input stream fed one key press at a time.
+///
+/// The `Executor` reads its input through any `Read` type, so a `KeyInput` lets the `Read`
+/// instruction take single key presses from a source such as a terminal in raw mode instead of
+/// whole lines. Every key is turned into its byte, and keys that are not ASCII are dropped
+/// because they do not fit in a single byte.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use headache::executor::Executor;
+/// use headache::input::KeyInput;
+///
+/// let keys = KeyInput::new("hé!".chars().map(Ok));
+/// let mut output = Vec::new();
+/// let mut executor = Executor::new(keys, &mut output);
+///
+/// executor.execute(",.,.").unwrap();
+///
+/// assert_eq!(&output, b"h!");
+/// ```
+pub struct KeyInput<Keys: Iterator<Item = std::io::Result<char>>> {
+    /// Source of the key presses, the end of the iterator is the end of the input.
+    keys: Keys,
+}
+
+impl<Keys: Iterator<Item = std::io::Result<char>>> KeyInput<Keys> {
+    /// Function to create a new `KeyInput` from a source of key presses.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - An iterator yielding every key press, or the error found while waiting for it.
+    pub fn new(keys: Keys) -> Self {
+        Self { keys }
+    }
+}
+
+impl<Keys: Iterator<Item = std::io::Result<char>>> Read for KeyInput<Keys> {
+    /// Function to read the byte of the next ASCII key press.
+    ///
+    /// A single byte is read per call, so no key press is waited for before the program asks
+    /// for it.
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        for key in self.keys.by_ref() {
+            let key = key?;
+            if key.is_ascii() {
+                buf[0] = key as u8;
+                return Ok(1);
+            }
+        }
+        Ok(0)
+    }
+}
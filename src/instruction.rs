@@ -1,9 +1,10 @@
 /// Enum representing the different instructions that can be used in a Brainfuck program.
 #[derive(Debug, Hash, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Instruction {
     /// Instruction to increase the data pointer (to point to the isize neighbor).
     Move(isize),
-    /// Instruction to increment (increase by isize) the byte at the data pointer.
+    /// Instruction to increment the cell at the data pointer by an 8-bit two's complement value.
     Add(u8),
     /// Instruction to output the byte at the data pointer as an ASCII character.
     Write,
@@ -13,6 +14,38 @@ pub enum Instruction {
     Loop(Vec<Instruction>),
     /// Set the current data to 0
     Clear,
+    /// Set the current data to the value that adding an 8-bit two's complement delta to 0 gives,
+    /// produced from a `Clear` followed by an `Add`
+    Set(u8),
+    /// Increment the cell at pointer + offset by an 8-bit two's complement value without moving
+    /// the data pointer, produced from the `Add`s between the `Move`s of straight-line code
+    AddAt { offset: isize, value: u8 },
     /// Add current data to value on pointer + offset and set current data to 0
-    MoveTo{ offset: isize },
-}
\ No newline at end of file
+    AddTo{ offset: isize },
+    /// Clear cells, moving the data pointer by stride after each one, until a zero cell is found
+    ClearScan { stride: isize },
+    /// Move the data pointer by stride until a zero cell is found
+    Seek { stride: isize },
+    /// Add current data multiplied by a factor to the value on pointer + offset of every target
+    /// and set current data to 0
+    MultiplyInto { targets: Vec<(isize, u8)> },
+    /// Print the data pointer and the cells around it to stderr, parsed from `#` in debug mode
+    DebugDump,
+    /// Output a constant string of bytes, produced by `parser::fold_constant_output`
+    WriteConst(Vec<u8>),
+}
+
+/// Function to drop a vector of Instructions without recursing into nested loops, so dropping a
+/// deeply nested program cannot overflow the call stack.
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to be dropped.
+pub fn drop_instructions(instructions: Vec<Instruction>) {
+    let mut pending = instructions;
+    while let Some(instruction) = pending.pop() {
+        if let Instruction::Loop(body) = instruction {
+            pending.extend(body);
+        }
+    }
+}
@@ -1,32 +1,234 @@
 #[cfg(test)]
 mod test;
 
+use std::io::{Cursor, Read, Write};
+use crate::error::Error;
+use crate::executor::Executor;
+use crate::stats::ProgramStats;
+
 /// Constant representing the size of the memory array used by the Brainfuck program.
 /// [DOC](http://brainfuck.org/brainfuck.html)
 pub const MEMORY_SIZE: usize = 30_000;
 
+mod bytecode;
+pub mod cell;
 pub mod error;
 pub mod executor;
-mod instruction;
-mod parser;
-#[cfg(target_arch="x86_64")]
+pub mod input;
+pub mod lint;
+pub mod instruction;
+pub mod parser;
+pub mod program;
+pub mod samples;
+pub mod stats;
+mod transpile;
+#[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
 pub mod compiler;
 
-pub fn test() -> u8 {
-    let mut arr = [0u8; MEMORY_SIZE];
-    code(arr.as_mut_ptr(), 0, unsafe {offset()});
-    arr[0]
+/// Function to run a Brainfuck program in the fastest way available on the target platform.
+///
+/// On x86_64 and aarch64 the program is compiled to native code and run, anywhere else, or if
+/// the machine code cannot be allocated, it is run by an `Executor`. Either way the output
+/// is the same. The program always starts on a fresh memory array, so the output it produces
+/// before its first input or loop is computed ahead of time, see `parser::fold_constant_output`.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be run.
+/// * `input` - An input stream to be used for reading data into the Brainfuck program.
+/// * `output` - An output stream to be used for writing data from the Brainfuck program.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// let mut output = Vec::new();
+/// headache::run(",+.", Cursor::new(b"@"), &mut output).unwrap();
+///
+/// assert_eq!(&output, b"A");
+/// ```
+///
+/// # Errors
+///
+/// This function returns an error if any of the following conditions are met:
+///
+/// * The given Brainfuck code cannot be parsed successfully. In this case, a `ParseError`
+///   is returned containing the specific parsing error that occurred.
+/// * An I/O error occurs while reading from the input stream or writing to the output stream.
+///   In this case, a `RuntimeError` is returned containing the underlying I/O error.
+pub fn run<Input: Read, Output: Write>(source: &str, input: Input, output: Output) -> Result<(), Error> {
+    #[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+    {
+        let (mut input, mut output) = (input, output);
+        let options = compiler::CompileOptions { fold_constant_output: true, ..compiler::CompileOptions::default() };
+        match compiler::compile_with_options(source, &mut input, &mut output, options) {
+            Ok(executable) => return executable.run(),
+            Err(Error::CompileError(_)) => {}
+            Err(err) => return Err(err),
+        }
+        run_interpreted(source, input, output)
+    }
+    #[cfg(not(any(target_arch="x86_64", target_arch="aarch64")))]
+    run_interpreted(source, input, output)
 }
 
-#[inline(never)]
-extern "sysv64" fn code(mem: *mut u8, index: usize, offset: isize) {
-    unsafe {
-        let to = ((index as isize + offset) % MEMORY_SIZE as isize + MEMORY_SIZE as isize) % MEMORY_SIZE as isize;
-        *mem.add(to as usize) += *mem.add(index);
-        *mem.add(index) = 0;
+/// Function to run a Brainfuck program followed by its own input, like `run` does.
+///
+/// The source is split at its first unescaped `!` with `parser::split_input`, and the bytes
+/// after it are the input of the program. A source without a `!` reads from `input` instead.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code, optionally followed by `!` and its input.
+/// * `input` - An input stream to be used when the source carries no input.
+/// * `output` - An output stream to be used for writing data from the Brainfuck program.
+///
+/// # Example
+///
+/// ```
+/// use std::io::stdin;
+///
+/// let mut output = Vec::new();
+/// headache::run_with_embedded_input(",.!A", stdin(), &mut output).unwrap();
+///
+/// assert_eq!(&output, b"A");
+/// ```
+///
+/// # Errors
+///
+/// This function returns the same errors as `run`.
+pub fn run_with_embedded_input<Input: Read, Output: Write>(source: &str, input: Input, output: Output) -> Result<(), Error> {
+    match parser::split_input(source) {
+        Some((program, embedded)) => run(program, Cursor::new(embedded), output),
+        None => run(source, input, output),
     }
 }
 
-extern "C" {
-    fn offset() -> isize;
-}
\ No newline at end of file
+/// Function to run a Brainfuck program on an in-memory input and return the output it produced.
+///
+/// The program is run by an `Executor` with the default configuration, reading from `input`
+/// and writing to a vector, which saves setting up the streams in tests and quick experiments.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be run.
+/// * `input` - The bytes the program reads as its input.
+///
+/// # Example
+///
+/// ```
+/// let output = headache::run_capture("+[-->-[>>+>-----<<]<--<---]>-.>>>+.>>..+++[.>]<<<<.+++.------.<<-.>>>>+.", b"").unwrap();
+///
+/// assert_eq!(output, b"Hello, World!");
+/// ```
+///
+/// # Errors
+///
+/// This function returns the same errors as `Executor::execute`, the output produced before
+/// the error is lost.
+pub fn run_capture(source: &str, input: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    Executor::new(input, &mut output).execute(source)?;
+    Ok(output)
+}
+
+/// Function to run a Brainfuck program with an `Executor`, the fallback path of `run`.
+fn run_interpreted<Input: Read, Output: Write>(source: &str, input: Input, output: Output) -> Result<(), Error> {
+    let instructions = parser::fold_constant_output(parser::parse(source)?);
+    let result = Executor::new(input, output).execute_instructions(&instructions);
+    instruction::drop_instructions(instructions);
+    result
+}
+
+/// Function to check that a Brainfuck program is valid and gather its stats without running it.
+///
+/// The program is parsed and optimized like it is for the `Executor`, so nothing is read or
+/// written, and the stats of the resulting instructions are returned, see `ProgramStats`.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be checked.
+///
+/// # Example
+///
+/// ```
+/// let stats = headache::validate(",[.,]").unwrap();
+///
+/// assert!(stats.has_reads());
+/// assert_eq!(stats.writes, 1);
+/// assert_eq!(stats.max_loop_depth, 1);
+/// ```
+///
+/// # Errors
+///
+/// This function returns a `ParseError` if the given Brainfuck code cannot be parsed.
+pub fn validate(source: &str) -> Result<ProgramStats, Error> {
+    let instructions = parser::parse(source)?;
+    let stats = ProgramStats::of(&instructions);
+    instruction::drop_instructions(instructions);
+    Ok(stats)
+}
+
+/// Function to translate a Brainfuck program into the source code of a self-contained C program.
+///
+/// The program is parsed and optimized like it is for the `Executor`, then every instruction is
+/// translated into C statements working on a memory array of `MEMORY_SIZE` `unsigned char` cells.
+/// The data pointer wraps around the ends of the memory array, and the output produced before the
+/// first input or loop is computed ahead of time, see `parser::fold_constant_output`. The C
+/// program exits with status 1 if a byte cannot be read, including at the end of the input, or
+/// written.
+///
+/// # Arguments
+///
+/// * `source` - A string containing Brainfuck source code to be translated.
+///
+/// # Example
+///
+/// ```
+/// let c = headache::transpile_c(",[.,]").unwrap();
+///
+/// assert!(c.contains("int main(void)"));
+/// assert!(c.contains("while (mem[p]) {"));
+/// ```
+///
+/// # Errors
+///
+/// This function returns a `ParseError` if the given Brainfuck code cannot be parsed.
+pub fn transpile_c(source: &str) -> Result<String, Error> {
+    let instructions = parser::fold_constant_output(parser::parse(source)?);
+    let c = transpile::c_program(&instructions);
+    instruction::drop_instructions(instructions);
+    Ok(c)
+}
+
+/// Function to run a single `AddTo` primitive on a fresh tape of `N` cells.
+///
+/// The first cell is set to 1 and then moved to the cell at `offset`, wrapping around the
+/// tape boundaries like the executor does. The resulting tape is returned.
+///
+/// # Arguments
+///
+/// * `offset` - Offset from the first cell of the cell that receives the value.
+///
+/// # Example
+///
+/// ```
+/// let tape = headache::run_single::<4>(-1);
+///
+/// assert_eq!(tape, [0, 0, 0, 1]);
+/// ```
+pub fn run_single<const N: usize>(offset: isize) -> [u8; N] {
+    let mut tape = [0u8; N];
+    tape[0] = 1;
+    add_to(&mut tape, 0, offset);
+    tape
+}
+
+/// Function to add the cell at `index` to the cell at `index + offset` and clear it.
+fn add_to(tape: &mut [u8], index: usize, offset: isize) {
+    let len = tape.len() as isize;
+    let to = ((index as isize + offset) % len + len) % len;
+    tape[to as usize] = tape[to as usize].wrapping_add(tape[index]);
+    tape[index] = 0;
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use crate::error::ParserError;
+use crate::instruction::drop_instructions;
+use crate::parser::parse;
+
+/// Enum representing the warnings the linter can report.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Warning {
+    /// A cell is written at `position`, but its value is never output or used as a loop guard
+    /// before being overwritten or before the program ends.
+    DeadWrite { position: usize },
+}
+
+impl Warning {
+    /// Function to get the byte offset in the source where the warning was found.
+    pub fn position(&self) -> usize {
+        match self {
+            Warning::DeadWrite { position } => *position,
+        }
+    }
+}
+
+/// Function to check a Brainfuck source code string for likely mistakes.
+///
+/// The analysis is best-effort: it only tracks cells through straight-line code, so any loop
+/// is assumed to observe every cell written before it, and the writes done inside loops are
+/// never reported.
+///
+/// # Arguments
+///
+/// * `source` - A string slice containing the Brainfuck source code.
+///
+/// # Returns
+///
+/// * A Result containing either the warnings sorted by position or a `ParserError` if the
+///   source cannot be parsed.
+pub fn lint(source: &str) -> Result<Vec<Warning>, ParserError> {
+    drop_instructions(parse(source)?);
+
+    let mut warnings = vec![];
+    // Offset of the data pointer relative to where the last loop left it.
+    let mut offset = 0isize;
+    // Position of the first unobserved write of every written cell, by offset.
+    let mut pending: HashMap<isize, usize> = HashMap::new();
+    // Depth of the loop currently being skipped, 0 when outside of any loop.
+    let mut depth = 0usize;
+
+    for (position, char) in source.char_indices() {
+        if depth > 0 {
+            match char {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        match char {
+            '>' => offset += 1,
+            '<' => offset -= 1,
+            '+' | '-' => {
+                pending.entry(offset).or_insert(position);
+            }
+            ',' => {
+                if let Some(write) = pending.insert(offset, position) {
+                    warnings.push(Warning::DeadWrite { position: write });
+                }
+            }
+            '.' => {
+                pending.remove(&offset);
+            }
+            '[' => {
+                pending.clear();
+                depth = 1;
+            }
+            _ => {}
+        }
+    }
+
+    warnings.extend(pending.into_values().map(|position| Warning::DeadWrite { position }));
+    warnings.sort_by_key(Warning::position);
+    Ok(warnings)
+}
@@ -1,10 +1,39 @@
-use crate::error::ParserError;
+use std::collections::HashMap;
+use crate::bytecode::{lower, Op};
+use crate::error::{BytecodeError, ParserError};
 use crate::error::ParserError::{IncompleteLoop, UnexpectedToken};
 use crate::instruction::Instruction;
-use crate::instruction::Instruction::MoveTo;
+use crate::instruction::Instruction::{AddTo, ClearScan, MultiplyInto, Seek};
+use crate::MEMORY_SIZE;
+
+/// Enum representing how much the parser optimizes a program.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum OptLevel {
+    /// Every command is parsed into its own instruction, every loop into an `Instruction::Loop`.
+    None,
+    /// Runs of `+` and `-`, and of `>` and `<`, are folded into a single `Add` or `Move`.
+    Fold,
+    /// Runs are folded, common loops are replaced by `Clear`, `AddTo`, `ClearScan`, `Seek` and
+    /// `MultiplyInto` instructions, a `Clear` followed by an `Add` becomes a `Set`, dead
+    /// stores are removed and the `Add`s between `Move`s become `AddAt`s, see `fuse_moves`.
+    #[default]
+    Full,
+}
+
+/// Struct representing the options of `parse_with_options`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct ParseOptions {
+    /// Whether `#` is parsed as an `Instruction::DebugDump` instead of being a comment.
+    pub debug: bool,
+    /// How much the program is optimized, `OptLevel::Full` by default.
+    pub opt_level: OptLevel,
+}
 
 /// Function to parse a Brainfuck source code string into a vector of Instructions.
 ///
+/// Every character other than the eight Brainfuck commands is a comment, and every optimization
+/// is applied, see `parse_with_options` to parse `#` as a debug dump or to optimize less.
+///
 /// # Arguments
 ///
 /// * `source` - A string slice containing the Brainfuck source code.
@@ -12,52 +41,115 @@ use crate::instruction::Instruction::MoveTo;
 /// # Returns
 ///
 /// * A Result containing either a vector of Instructions or a `ParserError`.
+///
+/// # Example
+///
+/// ```
+/// use headache::instruction::Instruction;
+/// use headache::parser::parse;
+///
+/// let instructions = parse("++>[-]").unwrap();
+///
+/// assert_eq!(instructions, vec![Instruction::Add(2), Instruction::Move(1), Instruction::Clear]);
+/// ```
 pub fn parse(source: &str) -> Result<Vec<Instruction>, ParserError> {
+    parse_with_options(source, ParseOptions::default())
+}
+
+/// Function to parse a Brainfuck source code string into a vector of Instructions with the
+/// given options.
+///
+/// # Arguments
+///
+/// * `source` - A string slice containing the Brainfuck source code.
+/// * `options` - The options of the parser, see `ParseOptions`.
+///
+/// # Returns
+///
+/// * A Result containing either a vector of Instructions or a `ParserError`.
+///
+/// # Example
+///
+/// ```
+/// use headache::instruction::Instruction;
+/// use headache::parser::{parse_with_options, OptLevel, ParseOptions};
+///
+/// let options = ParseOptions { debug: true, ..ParseOptions::default() };
+/// let instructions = parse_with_options("+#", options).unwrap();
+///
+/// assert_eq!(instructions, vec![Instruction::Add(1), Instruction::DebugDump]);
+///
+/// let options = ParseOptions { opt_level: OptLevel::Fold, ..ParseOptions::default() };
+/// let instructions = parse_with_options("++[-]", options).unwrap();
+///
+/// assert_eq!(instructions, vec![Instruction::Add(2), Instruction::Loop(vec![Instruction::Add(255)])]);
+/// ```
+pub fn parse_with_options(source: &str, options: ParseOptions) -> Result<Vec<Instruction>, ParserError> {
+    // The brackets are known to match below, so no tree is built for a source that cannot be parsed.
+    check_brackets(source)?;
+    let fold = options.opt_level != OptLevel::None;
+    let full = options.opt_level == OptLevel::Full;
     let mut contexts = vec![vec![]];
 
     for char in source.chars() {
         let instruction = match char {
             '>' => {
-                if let Some(Instruction::Move(n)) = contexts.last_mut().unwrap().last_mut() {
+                if let (true, Some(Instruction::Move(n))) = (fold, contexts.last_mut().unwrap().last_mut()) {
                     *n += 1;
                     continue;
                 }
                 Instruction::Move(1)
             }
             '<' => {
-                if let Some(Instruction::Move(n)) = contexts.last_mut().unwrap().last_mut() {
+                if let (true, Some(Instruction::Move(n))) = (fold, contexts.last_mut().unwrap().last_mut()) {
                     *n -= 1;
                     continue;
                 }
                 Instruction::Move(-1)
             }
+            // Runs of '+' and '-' are only folded while their sum fits in an `i8`, so cells
+            // wider than a byte get the exact sum when the delta is sign extended.
             '+' => {
-                if let Some(Instruction::Add(n)) = contexts.last_mut().unwrap().last_mut() {
-                    *n += 1;
+                if let (true, Some(Instruction::Add(n) | Instruction::Set(n))) = (fold, contexts.last_mut().unwrap().last_mut()) {
+                    if let Some(sum) = (*n as i8).checked_add(1) {
+                        *n = sum as u8;
+                        continue;
+                    }
+                }
+                if let (true, Some(last @ Instruction::Clear)) = (full, contexts.last_mut().unwrap().last_mut()) {
+                    *last = Instruction::Set(1);
                     continue;
                 }
                 Instruction::Add(1)
             }
             '-' => {
-                if let Some(Instruction::Add(n)) = contexts.last_mut().unwrap().last_mut() {
-                    *n -= 1;
+                if let (true, Some(Instruction::Add(n) | Instruction::Set(n))) = (fold, contexts.last_mut().unwrap().last_mut()) {
+                    if let Some(sum) = (*n as i8).checked_sub(1) {
+                        *n = sum as u8;
+                        continue;
+                    }
+                }
+                if let (true, Some(last @ Instruction::Clear)) = (full, contexts.last_mut().unwrap().last_mut()) {
+                    *last = Instruction::Set(1u8.wrapping_neg());
                     continue;
                 }
                 Instruction::Add(1u8.wrapping_neg())
             }
             '.' => Instruction::Write,
             ',' => Instruction::Read,
+            '#' if options.debug => Instruction::DebugDump,
             '[' => {
                 contexts.push(vec![]);
                 continue;
             }
             ']' => {
-                let Some(instructions) = contexts.pop() else {
-                    return Err(UnexpectedToken);
-                };
-                let Some(current_context) = contexts.last_mut() else {
-                    return Err(UnexpectedToken);
-                };
+                let instructions = contexts.pop().unwrap();
+                let current_context = contexts.last_mut().unwrap();
+                if !full {
+                    current_context.push(Instruction::Loop(instructions));
+                    continue;
+                }
+                let instructions = eliminate_dead_stores(instructions);
                 match instructions[..] {
                     [Instruction::Add(n)] if n & 1 == 1 => {
                         current_context.push(Instruction::Clear);
@@ -65,24 +157,701 @@ pub fn parse(source: &str) -> Result<Vec<Instruction>, ParserError> {
                     }
                     [Instruction::Add(255), Instruction::Move(x), Instruction::Add(1), Instruction::Move(y)]
                     if x == -y => {
-                        current_context.push(MoveTo { offset: x });
+                        current_context.push(AddTo { offset: x });
+                        continue;
+                    }
+                    [Instruction::Move(stride)] => {
+                        current_context.push(Seek { stride });
+                        continue;
+                    }
+                    [Instruction::Clear, Instruction::Move(stride)] => {
+                        current_context.push(ClearScan { stride });
                         continue;
                     }
                     _ => {}
                 }
-                current_context.push(Instruction::Loop(instructions));
+                if let Some(instruction) = multiply_loop(&instructions) {
+                    current_context.push(instruction);
+                    continue;
+                }
+                current_context.push(Instruction::Loop(fuse_moves(instructions)));
                 continue;
             }
             _ => continue
         };
-        let Some(current_context) = contexts.last_mut() else {
-            return Err(IncompleteLoop);
+        contexts.last_mut().unwrap().push(instruction);
+    }
+    let result = contexts.pop().unwrap();
+    Ok(if full { fuse_moves(eliminate_dead_stores(result)) } else { result })
+}
+
+/// Function to check that every bracket of a Brainfuck source code string has a match, without
+/// parsing it.
+///
+/// The source is scanned once counting the depth of the loops, so it is cheap enough to run on
+/// every line of a REPL. The errors are the same `parse` returns: a `]` without a matching `[`
+/// is an `UnexpectedToken`, and a `[` that is never closed is an `IncompleteLoop` at the last
+/// unclosed bracket, the one the following lines would close first.
+///
+/// # Arguments
+///
+/// * `source` - A string slice containing the Brainfuck source code.
+///
+/// # Errors
+///
+/// This function returns a `ParserError` with the location of the first bracket out of balance.
+///
+/// # Example
+///
+/// ```
+/// use headache::error::ParserError;
+/// use headache::parser::check_brackets;
+///
+/// assert_eq!(check_brackets("+[>[-]<]"), Ok(()));
+/// assert!(matches!(check_brackets("+[>["), Err(ParserError::IncompleteLoop { position: 3, .. })));
+/// assert!(matches!(check_brackets("+]"), Err(ParserError::UnexpectedToken { position: 1, .. })));
+/// ```
+pub fn check_brackets(source: &str) -> Result<(), ParserError> {
+    // Locations of the '[' that are still open.
+    let mut opened_at = vec![];
+    // Line and column of the current character.
+    let (mut line, mut col) = (1, 0);
+
+    for (position, char) in source.char_indices() {
+        if char == '\n' {
+            line += 1;
+            col = 0;
+            continue;
+        }
+        col += 1;
+        match char {
+            '[' => opened_at.push((position, line, col)),
+            ']' => {
+                opened_at.pop().ok_or(UnexpectedToken { position, line, col })?;
+            }
+            _ => {}
+        }
+    }
+    match opened_at.last() {
+        Some(&(position, line, col)) => Err(IncompleteLoop { position, line, col }),
+        None => Ok(()),
+    }
+}
+
+/// Function to remove the stores whose value is overwritten before it is used.
+///
+/// An `Add`, a `Clear` or a `Set` is dead when a later `Clear` or `Set` overwrites the same cell
+/// first. Cells are tracked by their offset through straight-line code, so any instruction
+/// other than `Move`, `Add`, `Clear`, `Set`, `Write` and `Read` is assumed to use every cell.
+/// `Read` does not overwrite the cell, it may be left unchanged at the end of the input. The
+/// stores left at the end of the program are kept, as the memory can be inspected once it
+/// finishes. The instructions made adjacent by the removed stores are folded again, a `Clear`
+/// followed by an `Add` becoming a `Set`.
+///
+/// Loop bodies are not visited, the parser optimizes every body when its loop is closed.
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to be optimized.
+fn eliminate_dead_stores(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut dead = vec![false; instructions.len()];
+    // Offset of the data pointer relative to the start of the straight-line code.
+    let mut offset = 0isize;
+    // Indices of the stores not used yet, by offset of their cell.
+    let mut pending: HashMap<isize, Vec<usize>> = HashMap::new();
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            Instruction::Move(n) => offset += n,
+            Instruction::Add(_) => pending.entry(offset).or_default().push(index),
+            Instruction::Clear | Instruction::Set(_) => {
+                for store in pending.insert(offset, vec![index]).unwrap_or_default() {
+                    dead[store] = true;
+                }
+            }
+            Instruction::Write | Instruction::Read => {
+                pending.remove(&offset);
+            }
+            _ => pending.clear(),
+        }
+    }
+
+    let mut result: Vec<Instruction> = vec![];
+    for (instruction, dead) in instructions.into_iter().zip(dead) {
+        if dead {
+            continue;
+        }
+        match (result.last_mut(), instruction) {
+            (Some(Instruction::Move(n)), Instruction::Move(m)) => {
+                *n += m;
+                if *n == 0 {
+                    result.pop();
+                }
+            }
+            // Adds are folded with the same `i8` limit as the parser does.
+            (Some(Instruction::Add(n)), Instruction::Add(m)) if (*n as i8).checked_add(m as i8).is_some() => {
+                *n = n.wrapping_add(m);
+                if *n == 0 {
+                    result.pop();
+                }
+            }
+            (Some(Instruction::Set(n)), Instruction::Add(m)) if (*n as i8).checked_add(m as i8).is_some() => {
+                *n = n.wrapping_add(m);
+            }
+            (Some(last @ Instruction::Clear), Instruction::Add(m)) => *last = Instruction::Set(m),
+            (_, instruction) => result.push(instruction),
+        }
+    }
+    result
+}
+
+/// Function to turn the `Add`s between the `Move`s of straight-line code into `AddAt`s.
+///
+/// Every run of `Move` and `Add` instructions is a segment, walked tracking the offset of the
+/// data pointer from where the segment started. The `Add`s to the same cell are folded with the
+/// same `i8` limit as the parser does, then the segment is rewritten as an `AddAt` for every
+/// cell but the ones where it starts and ends, which keep their `Add`, around a single `Move`
+/// to the cell where it ends. A segment with `AddAt`s always ends with its net `Move`, even a
+/// `Move(0)`, so `>+>+<<` becomes two `AddAt`s and a `Move(0)`, while `>+.` is left as it was.
+/// The data pointer only moves when a loop or any other instruction needs it.
+///
+/// Loop bodies are not visited, the parser optimizes every body when its loop is closed.
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to be optimized.
+fn fuse_moves(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut result = vec![];
+    // Offset of the data pointer relative to the start of the segment.
+    let mut offset = 0isize;
+    // Deltas added by the segment, by offset of their cell.
+    let mut adds: Vec<(isize, u8)> = vec![];
+
+    for instruction in instructions {
+        match &instruction {
+            Instruction::Move(n) => offset += n,
+            Instruction::Add(n) => match adds.iter_mut().rev().find(|(at, _)| *at == offset) {
+                Some((_, sum)) if (*sum as i8).checked_add(*n as i8).is_some() => *sum = sum.wrapping_add(*n),
+                _ => adds.push((offset, *n)),
+            },
+            _ => {
+                push_segment(&mut result, offset, &adds);
+                offset = 0;
+                adds.clear();
+                result.push(instruction);
+            }
+        }
+    }
+    push_segment(&mut result, offset, &adds);
+    result
+}
+
+/// Function to append the instructions of a segment rewritten by `fuse_moves`.
+///
+/// # Arguments
+///
+/// * `result` - The instructions to which the segment will be appended.
+/// * `offset` - Offset of the data pointer at the end of the segment.
+/// * `adds` - Deltas added by the segment, by offset of their cell.
+fn push_segment(result: &mut Vec<Instruction>, offset: isize, adds: &[(isize, u8)]) {
+    let mut fused = false;
+    for &(at, value) in adds {
+        match (at, value) {
+            (_, 0) => {}
+            (at, _) if at == offset => {}
+            (0, value) => result.push(Instruction::Add(value)),
+            (at, value) => {
+                result.push(Instruction::AddAt { offset: at, value });
+                fused = true;
+            }
+        }
+    }
+    if offset != 0 || fused {
+        result.push(Instruction::Move(offset));
+    }
+    for &(at, value) in adds {
+        if at == offset && value != 0 {
+            result.push(Instruction::Add(value));
+        }
+    }
+}
+
+/// Function to replace the output a program produces before its first input or loop by an
+/// `Instruction::WriteConst`.
+///
+/// The start of the program is evaluated on a zeroed memory array of `MEMORY_SIZE` `u8` cells
+/// until the first `Read`, `DebugDump` or loop, including the scans of `ClearScan` and `Seek`,
+/// so the evaluation takes a single pass over the instructions. If it wrote anything, the
+/// evaluated instructions are replaced by a `WriteConst` with the whole output, followed by the
+/// instructions leaving the memory array and the data pointer as they were, so the rest of the
+/// program runs unchanged.
+///
+/// The result is only equivalent for a program that starts on a zeroed memory array, with the
+/// data pointer on the first cell, as `Executor::new` and `Executable::run` do. It must not be
+/// used with memory that is reused or preloaded, with cells other than `u8`, or with tape modes
+/// other than `TapeMode::Wrapping`.
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions of a whole program.
+///
+/// # Example
+///
+/// ```
+/// use headache::instruction::Instruction;
+/// use headache::parser::{fold_constant_output, parse};
+///
+/// let instructions = fold_constant_output(parse("++++++++[>++++++++<-]>+.+.,").unwrap());
+///
+/// assert_eq!(instructions[0], Instruction::WriteConst(b"AB".to_vec()));
+/// assert_eq!(instructions.last(), Some(&Instruction::Read));
+/// ```
+pub fn fold_constant_output(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut tape = ConstantTape { memory: vec![0; MEMORY_SIZE], index: 0, output: vec![] };
+    let evaluated = instructions.iter().take_while(|instruction| tape.apply(instruction)).count();
+    if tape.output.is_empty() {
+        return instructions;
+    }
+
+    let mut result = vec![Instruction::WriteConst(tape.output)];
+    let mut index = 0;
+    for (cell, &value) in tape.memory.iter().enumerate().filter(|(_, value)| **value != 0) {
+        if cell != index {
+            result.push(Instruction::Move(cell as isize - index as isize));
+        }
+        result.push(Instruction::Add(value));
+        index = cell;
+    }
+    if tape.index != index {
+        result.push(Instruction::Move(tape.index as isize - index as isize));
+    }
+    result.extend(instructions.into_iter().skip(evaluated));
+    result
+}
+
+/// Struct representing the known state of a program evaluated by `fold_constant_output`.
+struct ConstantTape {
+    /// Memory array of the program.
+    memory: Vec<u8>,
+    /// Index of the data pointer in the memory array.
+    index: usize,
+    /// Bytes written by the program.
+    output: Vec<u8>,
+}
+
+impl ConstantTape {
+    /// Function to evaluate an instruction.
+    ///
+    /// # Returns
+    ///
+    /// * Whether the instruction could be evaluated, false if it depends on the input, has
+    ///   other side effects or is a loop.
+    fn apply(&mut self, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::Move(delta) => self.index = self.offset_index(*delta),
+            Instruction::Add(n) => self.memory[self.index] = self.memory[self.index].wrapping_add(*n),
+            Instruction::AddAt { offset, value } => {
+                let to = self.offset_index(*offset);
+                self.memory[to] = self.memory[to].wrapping_add(*value);
+            }
+            Instruction::Write => self.output.push(self.memory[self.index]),
+            Instruction::WriteConst(constant) => self.output.extend(constant),
+            Instruction::Clear => self.memory[self.index] = 0,
+            Instruction::Set(n) => self.memory[self.index] = *n,
+            Instruction::AddTo { offset } => {
+                let to = self.offset_index(*offset);
+                self.memory[to] = self.memory[to].wrapping_add(self.memory[self.index]);
+                self.memory[self.index] = 0;
+            }
+            Instruction::MultiplyInto { targets } => {
+                let value = self.memory[self.index];
+                for &(offset, factor) in targets {
+                    let to = self.offset_index(offset);
+                    self.memory[to] = self.memory[to].wrapping_add(value.wrapping_mul(factor));
+                }
+                self.memory[self.index] = 0;
+            }
+            Instruction::Read | Instruction::DebugDump | Instruction::Loop(_) | ClearScan { .. } | Seek { .. } => {
+                return false
+            }
+        }
+        true
+    }
+
+    /// Function to compute the index `delta` cells away from the data pointer, wrapping around
+    /// the memory array like `TapeMode::Wrapping` does.
+    fn offset_index(&self, delta: isize) -> usize {
+        (self.index as isize + delta).rem_euclid(MEMORY_SIZE as isize) as usize
+    }
+}
+
+/// Function to split a Brainfuck source code string at its first unescaped `!`.
+///
+/// Many Brainfuck judges put the input of a program after a `!` that ends the program text.
+/// A `!` preceded by a `\` is escaped, so it is part of a comment like any other character.
+///
+/// # Arguments
+///
+/// * `source` - A string slice containing the Brainfuck source code.
+///
+/// # Returns
+///
+/// * The program text and the bytes after the `!`, or None if there is no unescaped `!`.
+///
+/// # Example
+///
+/// ```
+/// use headache::parser::split_input;
+///
+/// assert_eq!(split_input(",.!A"), Some((",.", &b"A"[..])));
+/// assert_eq!(split_input("+.\\!"), None);
+/// ```
+pub fn split_input(source: &str) -> Option<(&str, &[u8])> {
+    let mut escaped = false;
+    for (position, char) in source.char_indices() {
+        if char == '!' && !escaped {
+            return Some((&source[..position], &source.as_bytes()[position + 1..]));
+        }
+        escaped = char == '\\' && !escaped;
+    }
+    None
+}
+
+/// Function to parse a Brainfuck source code string followed by the input of the program.
+///
+/// The source is split with `split_input`: the text before the first unescaped `!` is parsed
+/// and the bytes after it are returned as the input, without being interpreted as Brainfuck.
+/// A source without a `!` is parsed like `parse` does, with an empty input.
+///
+/// # Arguments
+///
+/// * `source` - A string slice containing the Brainfuck source code and its input.
+///
+/// # Returns
+///
+/// * A Result containing either the Instructions and the input bytes or a `ParserError`.
+pub fn parse_with_input(source: &str) -> Result<(Vec<Instruction>, Vec<u8>), ParserError> {
+    let (program, input) = split_input(source).unwrap_or((source, &[]));
+    Ok((parse(program)?, input.to_vec()))
+}
+
+/// Function to lower the body of a multiply loop, like `[->+++>++<<]`, to a single instruction.
+///
+/// A multiply loop only moves the data pointer and adds to cells, returns the data pointer to
+/// where it started, and decrements the starting cell once per iteration.
+///
+/// # Returns
+///
+/// * The `MultiplyInto` instruction equivalent to the loop, an `AddTo` if the loop copies the
+///   cell to a single target, or None if the body is not a multiply loop.
+fn multiply_loop(body: &[Instruction]) -> Option<Instruction> {
+    let mut offset = 0isize;
+    let mut origin = 0u8;
+    let mut targets: Vec<(isize, u8)> = vec![];
+
+    for instruction in body {
+        match instruction {
+            Instruction::Move(n) => offset += n,
+            // Deltas are summed with the same `i8` limit as the parser does, as they are applied
+            // as signed bytes to cells that can be wider than a byte.
+            Instruction::Add(n) if offset == 0 => origin = (origin as i8).checked_add(*n as i8)? as u8,
+            Instruction::Add(n) => match targets.iter_mut().find(|(target, _)| *target == offset) {
+                Some((_, factor)) => *factor = (*factor as i8).checked_add(*n as i8)? as u8,
+                None => targets.push((offset, *n)),
+            },
+            _ => return None,
+        }
+    }
+    if offset != 0 || origin != 255 {
+        return None;
+    }
+
+    targets.retain(|&(_, factor)| factor != 0);
+    Some(match targets[..] {
+        [] => Instruction::Clear,
+        [(offset, 1)] => AddTo { offset },
+        _ => MultiplyInto { targets },
+    })
+}
+
+// Opcodes of the bytecode produced by `to_bytecode`.
+const OP_MOVE: u8 = 0;
+const OP_ADD: u8 = 1;
+const OP_WRITE: u8 = 2;
+const OP_READ: u8 = 3;
+const OP_LOOP_START: u8 = 4;
+const OP_LOOP_END: u8 = 5;
+const OP_CLEAR: u8 = 6;
+const OP_ADD_TO: u8 = 7;
+const OP_CLEAR_SCAN: u8 = 8;
+const OP_SEEK: u8 = 9;
+const OP_MULTIPLY_INTO: u8 = 10;
+const OP_DEBUG_DUMP: u8 = 11;
+const OP_WRITE_CONST: u8 = 12;
+const OP_SET: u8 = 13;
+const OP_ADD_AT: u8 = 14;
+
+/// Function to render a vector of Instructions as readable text, one instruction per line.
+///
+/// Loop bodies are indented under their `Loop` line, and offsets and deltas are signed, so it
+/// is easy to see what the optimizations did to a program, for example whether a `[-]` became
+/// a `Clear`. The bytes of `Add`, `AddAt`, `Set` and the factors of `MultiplyInto` are all shown
+/// as the signed byte they stand for, so `Add(255)` is `Add -1` and `Set(255)` is `Set -1`.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of Instructions to be rendered.
+///
+/// # Example
+///
+/// ```
+/// use headache::parser::{format_instructions, parse};
+///
+/// let text = format_instructions(&parse(",[.[-],]").unwrap());
+///
+/// assert_eq!(text, "Read\nLoop\n    Write\n    Clear\n    Read\n");
+/// ```
+pub fn format_instructions(instructions: &[Instruction]) -> String {
+    let mut text = String::new();
+    let mut depth = 0;
+    // The flat form is walked instead of the tree, so deeply nested loops cannot overflow the stack.
+    for op in lower(instructions) {
+        let line = match &op {
+            Op::JumpIfZero(_) => "Loop".to_string(),
+            Op::JumpIfNonZero(_) => {
+                depth -= 1;
+                continue;
+            }
+            Op::Instruction(instruction) => format_instruction(instruction),
         };
-        current_context.push(instruction);
+        for _ in 0..depth {
+            text.push_str("    ");
+        }
+        text.push_str(&line);
+        text.push('\n');
+        if let Op::JumpIfZero(_) = op {
+            depth += 1;
+        }
     }
-    if contexts.len() != 1 {
-        return Err(IncompleteLoop);
+    text
+}
+
+/// Function to render an instruction other than a loop, see `format_instructions`.
+fn format_instruction(instruction: &Instruction) -> String {
+    match instruction {
+        Instruction::Move(n) => format!("Move {n:+}"),
+        Instruction::Add(n) => format!("Add {:+}", *n as i8),
+        Instruction::AddAt { offset, value } => format!("AddAt {offset:+} {:+}", *value as i8),
+        Instruction::Write => "Write".to_string(),
+        Instruction::Read => "Read".to_string(),
+        Instruction::Loop(_) => unreachable!("loops are lowered to jumps"),
+        Instruction::Clear => "Clear".to_string(),
+        Instruction::Set(n) => format!("Set {:+}", *n as i8),
+        Instruction::AddTo { offset } => format!("AddTo {offset:+}"),
+        Instruction::ClearScan { stride } => format!("ClearScan {stride:+}"),
+        Instruction::Seek { stride } => format!("Seek {stride:+}"),
+        Instruction::MultiplyInto { targets } => {
+            let targets: Vec<String> = targets.iter().map(|(offset, factor)| format!("{offset:+} x{}", *factor as i8)).collect();
+            format!("MultiplyInto {}", targets.join(", "))
+        }
+        Instruction::DebugDump => "DebugDump".to_string(),
+        Instruction::WriteConst(constant) => format!("WriteConst \"{}\"", constant.escape_ascii()),
+    }
+}
+
+/// Function to encode a vector of Instructions into a compact bytecode.
+///
+/// Every instruction is encoded as a one byte opcode followed by its operands. Offsets and
+/// counts are encoded as variable-length integers, so most of them take a single byte, and
+/// loops are encoded as their body between a start and an end opcode. The bytecode can be
+/// turned back into the same instructions with `from_bytecode`, without parsing the source again.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of Instructions to be encoded.
+///
+/// # Example
+///
+/// ```
+/// use headache::parser::{from_bytecode, parse, to_bytecode};
+///
+/// let instructions = parse("++[>+<-]").unwrap();
+/// let bytecode = to_bytecode(&instructions);
+///
+/// assert_eq!(from_bytecode(&bytecode).unwrap(), instructions);
+/// ```
+pub fn to_bytecode(instructions: &[Instruction]) -> Vec<u8> {
+    let mut bytes = vec![];
+    encode_into(instructions, &mut bytes);
+    bytes
+}
+
+/// Function to append the bytecode of a vector of Instructions to `bytes`.
+fn encode_into(instructions: &[Instruction], bytes: &mut Vec<u8>) {
+    for instruction in instructions {
+        match instruction {
+            Instruction::Move(n) => {
+                bytes.push(OP_MOVE);
+                push_offset(bytes, *n);
+            }
+            Instruction::Add(n) => bytes.extend([OP_ADD, *n]),
+            Instruction::AddAt { offset, value } => {
+                bytes.push(OP_ADD_AT);
+                push_offset(bytes, *offset);
+                bytes.push(*value);
+            }
+            Instruction::Write => bytes.push(OP_WRITE),
+            Instruction::Read => bytes.push(OP_READ),
+            Instruction::Loop(body) => {
+                bytes.push(OP_LOOP_START);
+                encode_into(body, bytes);
+                bytes.push(OP_LOOP_END);
+            }
+            Instruction::Clear => bytes.push(OP_CLEAR),
+            Instruction::Set(n) => bytes.extend([OP_SET, *n]),
+            Instruction::AddTo { offset } => {
+                bytes.push(OP_ADD_TO);
+                push_offset(bytes, *offset);
+            }
+            Instruction::ClearScan { stride } => {
+                bytes.push(OP_CLEAR_SCAN);
+                push_offset(bytes, *stride);
+            }
+            Instruction::Seek { stride } => {
+                bytes.push(OP_SEEK);
+                push_offset(bytes, *stride);
+            }
+            Instruction::MultiplyInto { targets } => {
+                bytes.push(OP_MULTIPLY_INTO);
+                push_varint(bytes, targets.len() as u64);
+                for &(offset, factor) in targets {
+                    push_offset(bytes, offset);
+                    bytes.push(factor);
+                }
+            }
+            Instruction::DebugDump => bytes.push(OP_DEBUG_DUMP),
+            Instruction::WriteConst(constant) => {
+                bytes.push(OP_WRITE_CONST);
+                push_varint(bytes, constant.len() as u64);
+                bytes.extend(constant);
+            }
+        }
+    }
+}
+
+/// Function to append an unsigned integer to `bytes` as a LEB128 variable-length integer.
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Function to append an offset to `bytes`, zigzag encoded so small negative offsets stay short.
+fn push_offset(bytes: &mut Vec<u8>, offset: isize) {
+    let offset = offset as i64;
+    push_varint(bytes, ((offset << 1) ^ (offset >> 63)) as u64);
+}
+
+/// Function to decode the bytecode produced by `to_bytecode` into a vector of Instructions.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytecode to be decoded.
+///
+/// # Errors
+///
+/// This function returns an error if any of the following conditions are met:
+///
+/// * The bytecode ends in the middle of an instruction or with a loop still open. In this
+///   case, an `UnexpectedEnd` error is returned.
+/// * A byte where an instruction is expected is not an opcode, or closes a loop that was never
+///   opened. In this case, an `InvalidOpcode` error is returned.
+/// * An offset or a count does not fit in its type. In this case, an `Overflow` error is returned.
+pub fn from_bytecode(bytes: &[u8]) -> Result<Vec<Instruction>, BytecodeError> {
+    let mut reader = BytecodeReader { bytes, position: 0 };
+    let mut contexts = vec![vec![]];
+
+    while reader.position < bytes.len() {
+        let position = reader.position;
+        let instruction = match reader.byte()? {
+            OP_MOVE => Instruction::Move(reader.offset()?),
+            OP_ADD => Instruction::Add(reader.byte()?),
+            OP_ADD_AT => Instruction::AddAt { offset: reader.offset()?, value: reader.byte()? },
+            OP_WRITE => Instruction::Write,
+            OP_READ => Instruction::Read,
+            OP_LOOP_START => {
+                contexts.push(vec![]);
+                continue;
+            }
+            OP_LOOP_END if contexts.len() > 1 => Instruction::Loop(contexts.pop().unwrap()),
+            OP_CLEAR => Instruction::Clear,
+            OP_SET => Instruction::Set(reader.byte()?),
+            OP_ADD_TO => AddTo { offset: reader.offset()? },
+            OP_CLEAR_SCAN => ClearScan { stride: reader.offset()? },
+            OP_SEEK => Seek { stride: reader.offset()? },
+            OP_MULTIPLY_INTO => {
+                let count = reader.varint()?;
+                let mut targets = vec![];
+                for _ in 0..count {
+                    targets.push((reader.offset()?, reader.byte()?));
+                }
+                MultiplyInto { targets }
+            }
+            OP_DEBUG_DUMP => Instruction::DebugDump,
+            OP_WRITE_CONST => {
+                let count = reader.varint()?;
+                let mut constant = vec![];
+                for _ in 0..count {
+                    constant.push(reader.byte()?);
+                }
+                Instruction::WriteConst(constant)
+            }
+            opcode => return Err(BytecodeError::InvalidOpcode { opcode, position }),
+        };
+        contexts.last_mut().unwrap().push(instruction);
+    }
+    if contexts.len() > 1 {
+        return Err(BytecodeError::UnexpectedEnd { position: bytes.len() });
+    }
+    Ok(contexts.pop().unwrap())
+}
+
+/// Struct representing a cursor over the bytecode being decoded by `from_bytecode`.
+struct BytecodeReader<'a> {
+    bytes: &'a [u8],
+    /// Offset of the next byte to be read.
+    position: usize,
+}
+
+impl BytecodeReader<'_> {
+    /// Function to read a single byte.
+    fn byte(&mut self) -> Result<u8, BytecodeError> {
+        let byte = *self.bytes.get(self.position).ok_or(BytecodeError::UnexpectedEnd { position: self.position })?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    /// Function to read a LEB128 variable-length integer.
+    fn varint(&mut self) -> Result<u64, BytecodeError> {
+        let start = self.position;
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BytecodeError::Overflow { position: start })
+    }
+
+    /// Function to read a zigzag encoded offset.
+    fn offset(&mut self) -> Result<isize, BytecodeError> {
+        let start = self.position;
+        let value = self.varint()?;
+        let offset = (value >> 1) as i64 ^ -((value & 1) as i64);
+        isize::try_from(offset).map_err(|_| BytecodeError::Overflow { position: start })
     }
-    let result = contexts.pop().unwrap();
-    Ok(result)
 }
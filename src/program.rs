@@ -0,0 +1,41 @@
+use crate::error::Error;
+use crate::instruction::{drop_instructions, Instruction};
+use crate::parser::parse;
+
+/// Struct representing a parsed Brainfuck program.
+///
+/// A `Program` holds the optimized instruction list produced by the parser, so the same
+/// source can be executed several times without being parsed again.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Function to parse a string of Brainfuck code into a `Program`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - A string containing Brainfuck code.
+    ///
+    /// # Errors
+    ///
+    /// This function returns a `ParseError` if the given Brainfuck code cannot be parsed successfully.
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let instructions = parse(source)?;
+        Ok(Self { instructions })
+    }
+
+    /// Function to get the instructions of the program.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+}
+
+impl Drop for Program {
+    /// Function to drop the instructions of the program without recursing into nested loops,
+    /// see `instruction::drop_instructions`.
+    fn drop(&mut self) {
+        drop_instructions(std::mem::take(&mut self.instructions));
+    }
+}
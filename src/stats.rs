@@ -0,0 +1,102 @@
+use crate::bytecode::{lower, Op};
+use crate::instruction::Instruction;
+
+/// Struct representing what can be told about a Brainfuck program without running it.
+///
+/// The stats describe the optimized instructions, so a loop replaced by a `MultiplyInto` or a
+/// `Clear` counts as a single instruction and does not add to the loop nesting depth.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProgramStats {
+    /// Number of instructions, counting every loop and every instruction in its body.
+    pub instructions: usize,
+    /// Depth of the most deeply nested loop, 0 if the program has no loops.
+    pub max_loop_depth: usize,
+    /// Number of `Read` instructions.
+    pub reads: usize,
+    /// Number of `Write` and `WriteConst` instructions.
+    pub writes: usize,
+    /// Lowest and highest offset from the starting cell of the cells the program can reach, or
+    /// `None` if it depends on the data, because of a `Seek`, a `ClearScan` or a loop that does
+    /// not leave the data pointer where it found it.
+    pub tape_extent: Option<(isize, isize)>,
+}
+
+impl ProgramStats {
+    /// Function to gather the stats of a vector of Instructions.
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - A slice of Instructions to be analyzed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headache::parser::parse;
+    /// use headache::stats::ProgramStats;
+    ///
+    /// let stats = ProgramStats::of(&parse(">+[<,.>-]").unwrap());
+    ///
+    /// assert_eq!(stats.max_loop_depth, 1);
+    /// assert_eq!(stats.tape_extent, Some((0, 1)));
+    /// ```
+    pub fn of(instructions: &[Instruction]) -> ProgramStats {
+        let mut stats = ProgramStats { instructions: 0, max_loop_depth: 0, reads: 0, writes: 0, tape_extent: None };
+        let (mut low, mut high) = (0isize, 0isize);
+        let mut offset = 0isize;
+        let mut extent_known = true;
+        // Offset of the data pointer at the start of every loop being walked.
+        let mut loops = vec![];
+        // The flat form is walked instead of the tree, so deeply nested loops cannot overflow the stack.
+        for op in lower(instructions) {
+            let instruction = match op {
+                Op::JumpIfZero(_) => {
+                    stats.instructions += 1;
+                    loops.push(offset);
+                    stats.max_loop_depth = stats.max_loop_depth.max(loops.len());
+                    continue;
+                }
+                Op::JumpIfNonZero(_) => {
+                    let start = loops.pop().expect("lowered loops are balanced");
+                    extent_known &= start == offset;
+                    continue;
+                }
+                Op::Instruction(instruction) => instruction,
+            };
+            stats.instructions += 1;
+            match &instruction {
+                Instruction::Move(n) => offset += *n,
+                Instruction::Read => stats.reads += 1,
+                Instruction::Write | Instruction::WriteConst(_) => stats.writes += 1,
+                Instruction::AddAt { offset: to, .. } | Instruction::AddTo { offset: to } => {
+                    low = low.min(offset + to);
+                    high = high.max(offset + to);
+                }
+                Instruction::MultiplyInto { targets } => {
+                    for (to, _) in targets {
+                        low = low.min(offset + to);
+                        high = high.max(offset + to);
+                    }
+                }
+                Instruction::ClearScan { .. } | Instruction::Seek { .. } => extent_known = false,
+                _ => {}
+            }
+            low = low.min(offset);
+            high = high.max(offset);
+        }
+        if extent_known {
+            stats.tape_extent = Some((low, high));
+        }
+        stats
+    }
+
+    /// Function to tell whether the program reads any input.
+    pub fn has_reads(&self) -> bool {
+        self.reads > 0
+    }
+
+    /// Function to tell whether the program writes any output.
+    pub fn has_writes(&self) -> bool {
+        self.writes > 0
+    }
+}
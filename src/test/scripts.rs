@@ -1,11 +1,12 @@
-/// From https://en.wikipedia.org/wiki/Brainfuck
-pub const HELLO_WORLD: &str =
-    "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
-/// From https://en.wikipedia.org/wiki/Brainfuck
-pub const SHORTER_HELLO_WORLD: &str = "+[-->-[>>+>-----<<]<--<---]>-.>>>+.>>..+++[.>]<<<<.+++.------.<<-.>>>>+.";
-/// From https://en.wikipedia.org/wiki/Brainfuck
-pub const COMMENTED_HELLO_WORLD: &str = include_str!("scripts/hello_world.bf");
+pub use crate::samples::{COMMENTED_HELLO_WORLD, HELLO_WORLD, MANDELBROT, SHORTER_HELLO_WORLD};
+
 /// From https://en.wikipedia.org/wiki/Brainfuck
 pub const ADD: &str = include_str!("scripts/add.bf");
-/// From Erik Bosman
-pub const MANDELBROT: &str = include_str!("scripts/mandelbrot.bf");
\ No newline at end of file
+/// Clears a run of cells up to a zero sentinel, then prints the run and the cell past the sentinel
+pub const CLEAR_SCAN: &str = "+>+>+>>+<<<<[[-]>]<<<.>.>.>>.";
+/// Adds twice a cell to its left neighbour and three times to its right neighbour, then prints both
+pub const MULTIPLY: &str = "++>+++[-<++>>+++<]<.>>.";
+/// Adds a cell to the cell two to its right and its right neighbour to itself, then prints three cells
+pub const ADD_TO: &str = "+++++>++<[->>+<<]>[-<+>]<.>.>.";
+/// Seeks forward and backward through a run of cells to the zero cells around it, then prints them
+pub const SEEK: &str = ">+>+>+<<[>]<[<]>+++.>>>.";
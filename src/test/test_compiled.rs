@@ -1,9 +1,21 @@
-use crate::compiler::compile;
+use crate::compiler::{cached_compile, compile, compile_with_options, compile_with_output_buffer, CellWidth, CompileOptions, TapePool, WrapMode};
+#[cfg(target_arch="x86_64")]
+use crate::compiler::{compile_to_asm, compile_to_object};
+use crate::error::Error;
+use crate::executor::Executor;
+use crate::instruction::Instruction;
+use crate::parser::parse;
+use crate::test::tests_executor::StopOnRead;
 use crate::test::MANDELBROT_RESULT;
+use crate::MEMORY_SIZE;
 use crate::test::scripts::{
-    ADD, COMMENTED_HELLO_WORLD, HELLO_WORLD, MANDELBROT, SHORTER_HELLO_WORLD,
+    ADD, ADD_TO, CLEAR_SCAN, COMMENTED_HELLO_WORLD, HELLO_WORLD, MANDELBROT, MULTIPLY, SEEK, SHORTER_HELLO_WORLD,
 };
-use std::io::stdin;
+use std::cell::RefCell;
+use std::io::{stdin, Cursor, Read, Write};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 fn execute_with_output(program: &str) -> String {
     let mut result = Vec::new();
@@ -41,4 +53,386 @@ fn test_sum() {
 fn test_mandelbrot() {
     let output = execute_with_output(MANDELBROT);
     assert_eq!(&output, MANDELBROT_RESULT);
-}
\ No newline at end of file
+}
+#[test]
+fn test_clear_scan() {
+    let output = execute_with_output(CLEAR_SCAN);
+    assert_eq!(&output, "\0\0\0\u{1}");
+}
+
+#[test]
+fn test_multiply() {
+    let output = execute_with_output(MULTIPLY);
+    assert_eq!(&output, "\u{8}\u{9}");
+}
+
+#[test]
+fn test_add_to() {
+    let output = execute_with_output(ADD_TO);
+    assert_eq!(&output, "\u{2}\0\u{5}");
+}
+
+#[test]
+fn test_seek() {
+    let output = execute_with_output(SEEK);
+    assert_eq!(&output, "\u{4}\0");
+}
+
+#[test]
+fn test_set() {
+    let instructions = parse("[-]+++++").unwrap();
+    assert_eq!(instructions, vec![Instruction::Set(5)]);
+    let mut tape = TapePool::new().acquire();
+    tape[0] = 9;
+    let mut output = Vec::new();
+    let mut stdin = stdin();
+    let executable = compile("[-]+++++.", &mut stdin, &mut output).unwrap();
+    executable.run_with_buffer(&mut tape).unwrap();
+    assert_eq!(&output, &[5]);
+    assert_eq!(tape[0], 5);
+}
+
+#[test]
+fn test_add_to_matches_interpreter() {
+    let program = "+++>++<<<++[->>>>+<<<<]>[<+>-]>>>>>>+++[-<<+>>]<<<<<[.>]";
+    assert_eq!(parse(program).unwrap().iter().filter(|i| matches!(i, Instruction::AddTo { .. })).count(), 3);
+
+    let mut interpreted = Vec::new();
+    Executor::new(Cursor::new(b""), &mut interpreted).execute(program).unwrap();
+    let mut compiled = Vec::new();
+    compile(program, &mut Cursor::new(b""), &mut compiled).unwrap().run().unwrap();
+    assert_eq!(compiled, interpreted);
+}
+
+#[test]
+fn test_add_at_matches_interpreter() {
+    let program = ">+>+<<,[>+>>+++<<<-.]>>->>++<<<<+>+<-<.>.>.>.>.";
+    assert!(parse(program).unwrap().iter().any(|i| matches!(i, Instruction::AddAt { .. })));
+
+    let mut interpreted = Vec::new();
+    Executor::new(Cursor::new(b"\x03"), &mut interpreted).execute(program).unwrap();
+    let mut compiled = Vec::new();
+    compile(program, &mut Cursor::new(b"\x03"), &mut compiled).unwrap().run().unwrap();
+    assert_eq!(compiled, interpreted);
+
+    let mut compiled = Vec::new();
+    let options = CompileOptions { wrap_mode: WrapMode::Assume, ..CompileOptions::default() };
+    compile_with_options(&format!(">{program}"), &mut Cursor::new(b"\x03"), &mut compiled, options).unwrap().run().unwrap();
+    assert_eq!(compiled, interpreted);
+}
+
+#[test]
+fn test_tape_pool_isolation() {
+    let mut pool = TapePool::new();
+    let mut stdin = stdin();
+    for _ in 0..3 {
+        let mut output = Vec::new();
+        let executable = compile("+++.>++.", &mut stdin, &mut output).unwrap();
+        pool.run(executable).unwrap();
+        assert_eq!(&output, &[3, 2]);
+        assert_eq!(pool.available(), 1);
+    }
+}
+
+#[test]
+fn test_run_with_buffer() {
+    let mut tape = TapePool::new().acquire();
+    tape[1] = 40;
+    let mut output = Vec::new();
+    let mut stdin = stdin();
+    let executable = compile(">++.", &mut stdin, &mut output).unwrap();
+    executable.run_with_buffer(&mut tape).unwrap();
+    assert_eq!(&output, &[42]);
+    assert_eq!(tape[1], 42);
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_compile_to_asm() {
+    let asm = compile_to_asm("+[-.]").unwrap();
+    assert!(asm.contains("; Add(1)\n    add     BYTE [r12 + r13], 1\n"));
+    assert!(asm.contains("loop_0:\n    cmp     BYTE [r12 + r13], 0\n    je      end_0\n"));
+    assert!(asm.contains("add     BYTE [r12 + r13], -1\n"));
+    assert!(asm.contains("mov     rsi, r14\n    call    QWORD [r14 + 8]\n"));
+    assert!(asm.contains("jne     loop_0\nend_0:\n"));
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_compile_to_asm_set() {
+    let asm = compile_to_asm("[-]+++++").unwrap();
+    assert!(asm.contains("; Set(5)\n    mov     BYTE [r12 + r13], 5\n"));
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_compile_to_asm_add_at() {
+    let asm = compile_to_asm(">>-<<").unwrap();
+    assert!(asm.contains("; AddAt { offset: 2, value: 255 }\n"));
+    assert!(asm.contains("    add     BYTE [r12 + rcx], -1\n"));
+}
+
+#[cfg(all(target_arch="x86_64", target_os = "linux"))]
+#[test]
+fn test_compile_to_object() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let dir = std::env::temp_dir();
+    let object = dir.join("headache_test_compile_to_object.o");
+    let binary = dir.join("headache_test_compile_to_object");
+    compile_to_object(&format!("{SHORTER_HELLO_WORLD},.,."), &object).unwrap();
+
+    let linked = Command::new("cc").arg(&object).arg("-o").arg(&binary).status().unwrap();
+    assert!(linked.success());
+
+    let mut child = Command::new(&binary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"!?").unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&object).unwrap();
+    std::fs::remove_file(&binary).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"Hello, World!!?");
+}
+
+/// Output stream counting the writes it gets.
+struct CountingWriter {
+    output: Vec<u8>,
+    writes: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_output_buffer_saves_writes() {
+    let count_writes = |buffer_size| {
+        let mut output = CountingWriter { output: vec![], writes: 0 };
+        compile_with_output_buffer(MANDELBROT, &mut stdin(), &mut output, buffer_size).unwrap().run().unwrap();
+        assert_eq!(std::str::from_utf8(&output.output).unwrap(), MANDELBROT_RESULT);
+        output.writes
+    };
+    assert_eq!(count_writes(1), MANDELBROT_RESULT.len());
+    assert_eq!(count_writes(1024), MANDELBROT_RESULT.len().div_ceil(1024));
+}
+
+/// Input and output streams logging every read and write, to check their order.
+#[derive(Clone, Default)]
+struct Log(Rc<RefCell<Vec<String>>>);
+
+impl Read for Log {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().push("read".to_string());
+        buf[0] = b'!';
+        Ok(1)
+    }
+}
+
+impl Write for Log {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().push(String::from_utf8_lossy(buf).to_string());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_output_is_written_before_reading() {
+    let log = Log::default();
+    let (mut input, mut output) = (log.clone(), log.clone());
+    compile("++++++++[>++++++++<-]>+.+.,.", &mut input, &mut output).unwrap().run().unwrap();
+    assert_eq!(*log.0.borrow(), vec!["AB", "read", "!"]);
+}
+
+fn execute_assuming_in_bounds(program: &str) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    let options = CompileOptions { wrap_mode: WrapMode::Assume, ..CompileOptions::default() };
+    compile_with_options(program, &mut Cursor::new(b""), &mut output, options)?.run()?;
+    Ok(output)
+}
+
+#[test]
+fn test_wrap_mode_assume() {
+    for program in [MANDELBROT, HELLO_WORLD, ADD_TO, MULTIPLY, SEEK, CLEAR_SCAN] {
+        assert_eq!(String::from_utf8(execute_assuming_in_bounds(program).unwrap()).unwrap(), execute_with_output(program));
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn test_wrap_mode_assume_checks_bounds() {
+    assert!(matches!(execute_assuming_in_bounds("+.<+"), Err(Error::PointerOutOfBounds { index: -1 })));
+    let far_right = format!("{}>[<]+", ">".repeat(MEMORY_SIZE - 1));
+    assert!(matches!(execute_assuming_in_bounds(&far_right), Err(Error::PointerOutOfBounds { index }) if index == MEMORY_SIZE as isize));
+    assert!(matches!(execute_assuming_in_bounds("+[>>+<<-]>>[<<<+>>>-]"), Err(Error::PointerOutOfBounds { index: -1 })));
+}
+
+#[test]
+fn test_stop_flag_flushes_output() {
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let mut input = StopOnRead(stop_flag.clone());
+    let mut output = Vec::new();
+    let mut executable = compile(",+.[.]", &mut input, &mut output).unwrap();
+    executable.set_stop_flag(Some(stop_flag));
+    assert!(matches!(executable.run(), Err(Error::Interrupted)));
+    assert_eq!(output, vec![2]);
+}
+
+#[test]
+fn test_fold_constant_output_compiled() {
+    let options = CompileOptions { fold_constant_output: true, ..CompileOptions::default() };
+    for program in [HELLO_WORLD, SHORTER_HELLO_WORLD, COMMENTED_HELLO_WORLD, MANDELBROT] {
+        let mut output = Vec::new();
+        compile_with_options(program, &mut Cursor::new(b""), &mut output, options).unwrap().run().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), execute_with_output(program));
+    }
+    // The constant is written through the same buffer as the rest of the output.
+    let mut output = Vec::new();
+    let options = CompileOptions { output_buffer_size: 4, ..options };
+    compile_with_options(&format!("{HELLO_WORLD},.."), &mut Cursor::new(b"!"), &mut output, options).unwrap().run().unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "Hello World!\n!!");
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_machine_code() {
+    let (mut input, mut output) = (Cursor::new(b""), Vec::new());
+    let executable = compile(HELLO_WORLD, &mut input, &mut output).unwrap();
+    let code = executable.machine_code().to_vec();
+    // push rbp; mov rbp, rsp
+    assert_eq!(&code[..4], &[0x55, 0x48, 0x89, 0xe5]);
+    executable.run().unwrap();
+    let mut output_again = Vec::new();
+    let executable = compile(HELLO_WORLD, &mut input, &mut output_again).unwrap();
+    assert_eq!(executable.machine_code(), &code[..]);
+    assert_eq!(&output, b"Hello World!\n");
+}
+
+#[test]
+fn test_cached_compile() {
+    let cache_dir = std::env::temp_dir().join(format!("headache_test_cache_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let cached_output = |source: &str| {
+        let (mut input, mut output) = (Cursor::new(b""), Vec::new());
+        cached_compile(source, &cache_dir, &mut input, &mut output).unwrap().run().unwrap();
+        output
+    };
+    let files = || std::fs::read_dir(&cache_dir).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+
+    // The first call compiles the program and stores its machine code.
+    assert_eq!(cached_output(HELLO_WORLD), b"Hello World!\n");
+    let hello_world = files();
+    assert_eq!(hello_world.len(), 1);
+    assert_eq!(hello_world[0].extension().unwrap(), "bin");
+    let stored = std::fs::read(&hello_world[0]).unwrap();
+    // The second call loads it.
+    assert_eq!(cached_output(HELLO_WORLD), b"Hello World!\n");
+    assert_eq!(files(), hello_world);
+    assert_eq!(std::fs::read(&hello_world[0]).unwrap(), stored);
+
+    // A file holding the machine code of another program, as after a hash collision, is replaced.
+    assert_eq!(cached_output("++++++++[>++++++++<-]>+."), b"A");
+    let other = files().into_iter().find(|path| *path != hello_world[0]).unwrap();
+    std::fs::copy(&other, &hello_world[0]).unwrap();
+    assert_eq!(cached_output(HELLO_WORLD), b"Hello World!\n");
+    assert_eq!(std::fs::read(&hello_world[0]).unwrap(), stored);
+
+    // A file written by another version of the crate is replaced.
+    let version = env!("CARGO_PKG_VERSION");
+    let mut stale = stored.clone();
+    let at = stale.windows(version.len()).position(|window| window == version.as_bytes()).unwrap();
+    stale[at] ^= 0xff;
+    std::fs::write(&hello_world[0], &stale).unwrap();
+    assert_eq!(cached_output(HELLO_WORLD), b"Hello World!\n");
+    assert_eq!(std::fs::read(&hello_world[0]).unwrap(), stored);
+
+    // So is a truncated file.
+    std::fs::write(&hello_world[0], &stored[..stored.len() - 1]).unwrap();
+    assert_eq!(cached_output(HELLO_WORLD), b"Hello World!\n");
+    assert_eq!(std::fs::read(&hello_world[0]).unwrap(), stored);
+
+    std::fs::remove_dir_all(&cache_dir).unwrap();
+}
+
+#[cfg(target_arch="x86_64")]
+fn execute_with_cell_width(program: &str, input: &[u8], cell_width: CellWidth) -> Vec<u8> {
+    let mut output = Vec::new();
+    let options = CompileOptions { cell_width, ..CompileOptions::default() };
+    compile_with_options(program, &mut Cursor::new(input), &mut output, options).unwrap().run().unwrap();
+    output
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_16_bit_cells_compiled() {
+    // The cell holds 300, its low byte is written first.
+    let output = execute_with_cell_width(&format!("{}.", "+".repeat(300)), b"", CellWidth::U16);
+    assert_eq!(&output, &[0x2c, 0x01]);
+    let output = execute_with_cell_width(",-.>-.", b"\x2c\x01", CellWidth::U16);
+    assert_eq!(&output, &[0x2b, 0x01, 0xff, 0xff]);
+    let output = execute_with_cell_width("[-]-.", b"", CellWidth::U32);
+    assert_eq!(&output, &[0xff; 4]);
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_16_bit_cells_multiply_loop_compiled() {
+    let output = execute_with_cell_width(&format!("++[->{}<]>.", "+".repeat(200)), b"", CellWidth::U16);
+    assert_eq!(&output, &400u16.to_le_bytes());
+    let output = execute_with_cell_width(&format!("++[->{}>+<<]>.>.", "+".repeat(200)), b"", CellWidth::U32);
+    assert_eq!(&output, &[400u32.to_le_bytes(), 2u32.to_le_bytes()].concat());
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_16_bit_cells_ignore_fold_constant_output() {
+    let options = CompileOptions { fold_constant_output: true, cell_width: CellWidth::U16, ..CompileOptions::default() };
+    let mut output = Vec::new();
+    compile_with_options(&format!("{}.", "+".repeat(256)), &mut Cursor::new(b""), &mut output, options).unwrap().run().unwrap();
+    assert_eq!(&output, &[0x00, 0x01]);
+    let mut output = Vec::new();
+    compile_with_options("++++++++[>++++++++<-]>[->++++++<]>.", &mut Cursor::new(b""), &mut output, options).unwrap().run().unwrap();
+    assert_eq!(&output, &[0x80, 0x01]);
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+fn test_wide_cells_match_interpreter() {
+    let programs = ["+++[->++>---<<]>.>.", "-[>+>+<<-]>.>.", ">-->+<<.>.>.", "++++[->>[-]-<<]>.>.", ADD_TO, MULTIPLY, SEEK, CLEAR_SCAN, HELLO_WORLD];
+    for program in programs {
+        let mut interpreted = Vec::new();
+        Executor::<_, _, u16>::with_cells(Cursor::new(b""), &mut interpreted, MEMORY_SIZE).execute(program).unwrap();
+        assert_eq!(execute_with_cell_width(program, b"", CellWidth::U16), interpreted, "{program}");
+
+        let mut interpreted = Vec::new();
+        Executor::<_, _, u32>::with_cells(Cursor::new(b""), &mut interpreted, MEMORY_SIZE).execute(program).unwrap();
+        assert_eq!(execute_with_cell_width(program, b"", CellWidth::U32), interpreted, "{program}");
+    }
+}
+
+#[cfg(target_arch="x86_64")]
+#[test]
+#[should_panic(expected = "8-bit cells")]
+fn test_wide_cells_do_not_fit_in_buffer() {
+    let (mut input, mut output) = (Cursor::new(b""), Vec::new());
+    let options = CompileOptions { cell_width: CellWidth::U16, ..CompileOptions::default() };
+    let executable = compile_with_options("+.", &mut input, &mut output, options).unwrap();
+    let _ = executable.run_with_buffer(&mut TapePool::new().acquire());
+}
@@ -0,0 +1,50 @@
+use crate::error::Error;
+use crate::executor::{AsyncExecutor, EofBehavior, ExecutorBuilder};
+use crate::test::scripts::{HELLO_WORLD, MULTIPLY};
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn test_echo_over_duplex() {
+    let (mut client_input, input) = duplex(64);
+    let (output, mut client_output) = duplex(64);
+    let mut executor = AsyncExecutor::new(input, output);
+
+    client_input.write_all(b"Hi").await.unwrap();
+    executor.execute(",.,.").await.unwrap();
+    let mut echoed = [0u8; 2];
+    client_output.read_exact(&mut echoed).await.unwrap();
+    assert_eq!(&echoed, b"Hi");
+
+    // The input runs out once the client closes its end.
+    drop(client_input);
+    assert!(matches!(executor.execute(",.").await, Err(Error::RuntimeError(_))));
+}
+
+#[tokio::test]
+async fn test_matches_executor() {
+    for program in [HELLO_WORLD, MULTIPLY] {
+        let mut output = Vec::new();
+        let mut executor = AsyncExecutor::new(&b""[..], &mut output);
+        executor.execute(program).await.unwrap();
+        let (memory, index) = (executor.executor().memory.clone(), executor.executor().index);
+
+        let mut expected = Vec::new();
+        let mut sync = ExecutorBuilder::new().build(std::io::empty(), &mut expected);
+        sync.execute(program).unwrap();
+        assert_eq!(memory, sync.memory);
+        assert_eq!(index, sync.index);
+        assert_eq!(output, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_eof_behavior_and_step_limit() {
+    let builder = ExecutorBuilder::new().eof(EofBehavior::Zero);
+    let mut output = Vec::new();
+    let mut executor = AsyncExecutor::with_builder(builder, &b"ab"[..], &mut output);
+    executor.execute(",[.,]").await.unwrap();
+
+    executor.executor_mut().set_max_steps(Some(10));
+    assert!(matches!(executor.execute("+[]").await, Err(Error::StepLimitExceeded)));
+    assert_eq!(&output, b"ab");
+}
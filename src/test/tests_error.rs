@@ -0,0 +1,63 @@
+use crate::error::{Error, ParserError};
+use crate::instruction::Instruction;
+use crate::parser::parse;
+
+#[test]
+fn test_incomplete_loop_display() {
+    let err = ParserError::IncompleteLoop { position: 3, line: 1, col: 4 };
+    assert_eq!(
+        err.to_string(),
+        "All the '[' instructions must be closed with a ']' instruction (line 1, column 4)"
+    );
+    assert_eq!(Error::ParseError(err.clone()).to_string(), err.to_string());
+}
+
+#[test]
+fn test_error_into_boxed_error() {
+    let io = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "pipe closed");
+    let err: Box<dyn std::error::Error> = Box::new(Error::RuntimeError(io));
+    assert_eq!(err.source().unwrap().to_string(), "pipe closed");
+
+    let err: Box<dyn std::error::Error> = Error::StepLimitExceeded.into();
+    assert_eq!(err.to_string(), "The step limit was exceeded");
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn test_from_conversions() {
+    let err = ParserError::UnexpectedToken { position: 0, line: 1, col: 1 };
+    assert!(matches!(Error::from(err.clone()), Error::ParseError(parsed) if parsed == err));
+
+    let err: Error = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "pipe closed").into();
+    assert!(matches!(&err, Error::RuntimeError(io) if io.kind() == std::io::ErrorKind::BrokenPipe));
+
+    // The conversions let `?` mix the parser and I/O errors with the others.
+    fn parse_and_write(source: &str, output: &mut dyn std::io::Write) -> Result<Vec<Instruction>, Error> {
+        let instructions = parse(source)?;
+        output.write_all(b"parsed")?;
+        Ok(instructions)
+    }
+    assert!(matches!(parse_and_write("[", &mut Vec::new()), Err(Error::ParseError(ParserError::IncompleteLoop { .. }))));
+    assert!(matches!(parse_and_write("+", &mut &mut [0u8; 2][..]), Err(Error::RuntimeError(_))));
+    assert_eq!(parse_and_write("+", &mut Vec::new()).unwrap(), vec![Instruction::Add(1)]);
+}
+
+#[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+#[test]
+fn test_compile_error() {
+    use std::io::Cursor;
+    use crate::compiler::cached_compile;
+
+    // A cache directory that is a file cannot store the machine code.
+    let path = std::env::temp_dir().join(format!("headache_test_compile_error_{}", std::process::id()));
+    std::fs::write(&path, b"").unwrap();
+    let (mut input, mut output) = (Cursor::new(b""), Vec::new());
+    let result = cached_compile("+.", &path, &mut input, &mut output);
+    std::fs::remove_file(&path).unwrap();
+    let Err(err @ Error::CompileError(_)) = result else {
+        panic!("expected a compile error");
+    };
+    assert!(err.to_string().starts_with("Cannot compile the script: "));
+    let err: Box<dyn std::error::Error> = err.into();
+    assert!(err.source().is_some());
+}
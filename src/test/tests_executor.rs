@@ -0,0 +1,507 @@
+use crate::error::{Error, ParserError};
+use crate::executor::{CellOverflow, EofBehavior, Executor, ExecutorBuilder, Fuel, Profile, RunOutcome, Step, StepOutcome, TapeMode};
+use crate::instruction::Instruction;
+use crate::parser::{parse, parse_with_options, OptLevel, ParseOptions};
+use crate::program::Program;
+use crate::test::scripts::SHORTER_HELLO_WORLD;
+use crate::MEMORY_SIZE;
+use std::io::{BufWriter, Cursor, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+#[test]
+fn test_try_run_completed() {
+    let mut output = Vec::new();
+    let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    let program = Program::parse(SHORTER_HELLO_WORLD).unwrap();
+    assert!(matches!(executor.try_run(&program, 1_000_000), RunOutcome::Completed));
+    assert_eq!(&output, b"Hello, World!");
+}
+
+#[test]
+fn test_try_run_step_limit() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    let program = Program::parse("+[]").unwrap();
+    assert!(matches!(executor.try_run(&program, 10_000), RunOutcome::StepLimit));
+}
+
+#[test]
+fn test_with_capacity_does_not_wrap() {
+    let mut executor = Executor::with_capacity(Cursor::new(b""), Vec::new(), 40_000);
+    let program = format!("+{}++", ">".repeat(35_000));
+    executor.execute(&program).unwrap();
+    assert_eq!(executor.memory.len(), 40_000);
+    assert_eq!(executor.memory[0].0, 1);
+    assert_eq!(executor.memory[35_000].0, 2);
+    assert_eq!(executor.memory[5_000].0, 0);
+    assert_eq!(executor.index, 35_000);
+}
+
+#[test]
+fn test_with_capacity_wraps_at_capacity() {
+    let mut executor = Executor::with_capacity(Cursor::new(b""), Vec::new(), 10);
+    executor.execute("<+").unwrap();
+    assert_eq!(executor.index, 9);
+    assert_eq!(executor.memory[9].0, 1);
+}
+
+#[test]
+fn test_last_parse_error_position() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    assert!(executor.execute("+>[-[+]").is_err());
+    assert_eq!(executor.last_parse_error(), Some(&ParserError::IncompleteLoop { position: 2, line: 1, col: 3 }));
+    assert_eq!(executor.last_parse_error().unwrap().position(), 2);
+
+    assert!(executor.execute("+-]").is_err());
+    assert_eq!(executor.last_parse_error(), Some(&ParserError::UnexpectedToken { position: 2, line: 1, col: 3 }));
+
+    executor.execute("+").unwrap();
+    assert_eq!(executor.last_parse_error(), None);
+}
+
+#[test]
+fn test_failed_instruction() {
+    let mut executor = Executor::with_tape_mode(Cursor::new(b""), Vec::new(), TapeMode::Strict);
+    assert!(matches!(executor.execute("+<"), Err(Error::PointerOutOfBounds { index: -1 })));
+    assert_eq!(executor.failed_instruction(), Some(&Instruction::Move(-1)));
+
+    assert!(matches!(executor.execute("[,]+[-,]"), Err(Error::RuntimeError(_))));
+    assert_eq!(executor.failed_instruction(), Some(&Instruction::Read));
+
+    executor.set_max_steps(Some(10));
+    assert!(matches!(executor.execute("+[]"), Err(Error::StepLimitExceeded)));
+    assert_eq!(executor.failed_instruction(), None);
+
+    executor.set_max_steps(None);
+    executor.execute("[-]").unwrap();
+    assert_eq!(executor.failed_instruction(), None);
+}
+
+#[test]
+fn test_growable_tape() {
+    let mut output = Vec::new();
+    let mut executor = Executor::with_tape_mode(Cursor::new(b""), &mut output, TapeMode::Growable);
+    let program = format!("{}+++.", ">".repeat(100_000));
+    executor.execute(&program).unwrap();
+    assert_eq!(executor.index, 100_000);
+    assert!(executor.memory.len() > 100_000);
+    assert_eq!(executor.memory[100_000].0, 3);
+    assert_eq!(executor.memory[100_000 % MEMORY_SIZE].0, 0);
+    assert_eq!(&output, &[3]);
+}
+
+#[test]
+fn test_growable_tape_left_of_first_cell() {
+    let mut executor = Executor::with_tape_mode(Cursor::new(b""), Vec::new(), TapeMode::Growable);
+    let result = executor.execute(">><<<");
+    assert!(matches!(result, Err(Error::PointerOutOfBounds { index: -1 })));
+}
+
+#[test]
+fn test_strict_tape_left_of_first_cell() {
+    let mut executor = Executor::with_tape_mode(Cursor::new(b""), Vec::new(), TapeMode::Strict);
+    let result = executor.execute("+><<");
+    assert!(matches!(result, Err(Error::PointerOutOfBounds { index: -1 })));
+    assert_eq!(executor.pointer(), 0);
+}
+
+#[test]
+fn test_strict_tape_past_the_end() {
+    let mut executor = ExecutorBuilder::new().tape_cells(8).tape_mode(TapeMode::Strict).build(Cursor::new(b""), Vec::new());
+    executor.execute(">>>>>>>").unwrap();
+    assert!(matches!(executor.execute(">"), Err(Error::PointerOutOfBounds { index: 8 })));
+    executor.reset();
+    assert!(matches!(executor.execute("+[>+]"), Err(Error::PointerOutOfBounds { index: 8 })));
+    executor.reset();
+    assert!(matches!(executor.execute("+[->>>>>>>>+<<<<<<<<]"), Err(Error::PointerOutOfBounds { index: 8 })));
+    executor.reset();
+    assert!(matches!(executor.execute("+[-<+>]"), Err(Error::PointerOutOfBounds { index: -1 })));
+    // The target of a copy loop is not reached when the loop does not run.
+    executor.reset();
+    executor.execute("[-<+>][-<++>>>>>>>>>+<<<<<<<<]+.").unwrap();
+}
+
+#[test]
+fn test_fuel_shared_across_executes() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    let mut fuel = Fuel::new(10);
+    executor.execute_with_fuel("+++", &mut fuel).unwrap();
+    assert_eq!(fuel.remaining(), 9);
+    // One step to enter the loop and six per iteration: 19 steps in total.
+    let result = executor.execute_with_fuel("[>+.<-]", &mut fuel);
+    assert!(matches!(result, Err(Error::OutOfFuel)));
+    assert_eq!(fuel.remaining(), 0);
+}
+
+fn cell_after_eof(eof_behavior: EofBehavior) -> Result<u8, Error> {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    executor.set_eof_behavior(eof_behavior);
+    executor.execute("+++++++,")?;
+    Ok(executor.memory[0].0)
+}
+
+#[test]
+fn test_eof_behavior() {
+    assert_eq!(cell_after_eof(EofBehavior::Unchanged).unwrap(), 7);
+    assert_eq!(cell_after_eof(EofBehavior::Zero).unwrap(), 0);
+    assert_eq!(cell_after_eof(EofBehavior::NegativeOne).unwrap(), 255);
+    assert!(matches!(cell_after_eof(EofBehavior::Error), Err(Error::RuntimeError(_))));
+}
+
+#[test]
+fn test_16_bit_cells() {
+    let mut output = Vec::new();
+    let mut executor = Executor::<_, _, u16>::with_cells(Cursor::new(b"\x2c\x01"), &mut output, MEMORY_SIZE);
+    executor.execute(&format!("{}.>,-", "+".repeat(300))).unwrap();
+    assert_eq!(executor.memory[0].0, 300);
+    assert_eq!(executor.memory[1].0, 299);
+    assert_eq!(&output, &[0x2c, 0x01]);
+}
+
+#[test]
+fn test_16_bit_cells_multiply_loop() {
+    let mut executor = Executor::<_, _, u16>::with_cells(Cursor::new(b""), Vec::new(), MEMORY_SIZE);
+    executor.execute(&format!("++[->{}<]", "+".repeat(200))).unwrap();
+    assert_eq!(executor.memory[1].0, 400);
+    let mut executor = Executor::<_, _, u16>::with_cells(Cursor::new(b""), Vec::new(), MEMORY_SIZE);
+    executor.execute(&format!("+[{}>+<]", "+".repeat(255))).unwrap();
+    // The origin grows by 255 per iteration, 1 + 255 * 257 wraps to 0.
+    assert_eq!(executor.memory[0].0, 0);
+    assert_eq!(executor.memory[1].0, 257);
+}
+
+#[test]
+fn test_8_bit_cells_wrap() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    executor.execute(&"+".repeat(300)).unwrap();
+    assert_eq!(executor.memory[0].0, 44);
+}
+
+#[test]
+fn test_max_steps_stops_infinite_loop() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    executor.set_max_steps(Some(1_000));
+    assert!(matches!(executor.execute("+[]"), Err(Error::StepLimitExceeded)));
+    assert!(matches!(executor.execute("+[>+<]"), Err(Error::StepLimitExceeded)));
+
+    executor.set_max_steps(None);
+    executor.execute("[-]").unwrap();
+}
+
+#[test]
+fn test_memory_accessors() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    executor.execute("+++>++").unwrap();
+    assert_eq!(executor.memory()[0].0, 3);
+    assert_eq!(executor.memory()[1].0, 2);
+    assert_eq!(executor.pointer(), 1);
+    assert_eq!(executor.dump_nonzero(), vec![(0, 3), (1, 2)]);
+}
+
+#[test]
+fn test_reset() {
+    let mut output = Vec::new();
+    let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    executor.execute("+++>++>+.").unwrap();
+    executor.reset();
+    assert!(executor.memory().iter().all(|cell| cell.0 == 0));
+    assert_eq!(executor.pointer(), 0);
+
+    executor.execute("++.").unwrap();
+    assert_eq!(&output, &[1, 2]);
+}
+
+#[test]
+fn test_step_through_loop() {
+    let mut output = Vec::new();
+    let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    executor.load("++[-.]").unwrap();
+
+    let mut steps = vec![];
+    loop {
+        match executor.step().unwrap() {
+            StepOutcome::Executed { step, finished } => {
+                steps.push((step, executor.memory()[0].0));
+                if finished {
+                    break;
+                }
+            }
+            StepOutcome::Finished => unreachable!(),
+        }
+    }
+    assert_eq!(executor.step().unwrap(), StepOutcome::Finished);
+    assert_eq!(steps, vec![
+        (Step::Instruction(Instruction::Add(2)), 2),
+        (Step::LoopStart { entered: true }, 2),
+        (Step::Instruction(Instruction::Add(255)), 1),
+        (Step::Instruction(Instruction::Write), 1),
+        (Step::LoopEnd { repeated: true }, 1),
+        (Step::Instruction(Instruction::Add(255)), 0),
+        (Step::Instruction(Instruction::Write), 0),
+        (Step::LoopEnd { repeated: false }, 0),
+    ]);
+    assert_eq!(&output, &[1, 0]);
+}
+
+#[test]
+fn test_execute_profiled() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    let profile = executor.execute_profiled("++[>+.<-]").unwrap();
+    assert_eq!(profile, Profile {
+        moves: 4,
+        adds: 5,
+        writes: 2,
+        loops: 1,
+        loop_iterations: 2,
+        ..Profile::default()
+    });
+}
+
+#[test]
+fn test_set_instruction() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    let profile = executor.execute_profiled("++.[-]+++++").unwrap();
+    assert_eq!(executor.memory[0].0, 5);
+    assert_eq!(profile.sets, 1);
+    assert_eq!(profile.adds, 1);
+
+    let mut executor = ExecutorBuilder::new()
+        .cell_overflow(CellOverflow::Error)
+        .build(Cursor::new(b""), Vec::new());
+    assert!(matches!(executor.execute("+[-]-"), Err(Error::CellOverflow { index: 0 })));
+}
+
+#[test]
+fn test_execute_traced() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    let accesses = executor.execute_traced(">>>+").unwrap();
+    assert_eq!(accesses.len(), MEMORY_SIZE);
+    assert!(accesses[3] > 0);
+    assert_eq!(accesses[0], 0);
+
+    // Every test of a loop is an access, and so are the targets of an `AddTo`.
+    executor.reset();
+    let accesses = executor.execute_traced("++[>+<-]>[-<+>]+++[.-]").unwrap();
+    assert_eq!(&accesses[..3], &[3, 13, 0]);
+
+    // The counters start over on every traced run and are not kept by the others.
+    executor.reset();
+    executor.execute(">>>+").unwrap();
+    assert_eq!(executor.execute_traced("+").unwrap()[3], 1);
+
+    let mut executor = Executor::with_tape_mode(Cursor::new(b""), Vec::new(), TapeMode::Growable);
+    let accesses = executor.execute_traced(&format!("{}+", ">".repeat(MEMORY_SIZE))).unwrap();
+    assert_eq!(accesses.len(), executor.memory.len());
+    assert_eq!(accesses[MEMORY_SIZE], 1);
+}
+
+#[test]
+fn test_seek_stops_at_zero_cell() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    executor.execute("+>+>+<<[>]").unwrap();
+    assert_eq!(executor.pointer(), 3);
+    assert_eq!(executor.dump_nonzero(), vec![(0, 1), (1, 1), (2, 1)]);
+}
+
+/// Output stream counting the flushes it gets.
+#[derive(Default)]
+struct FlushCounter {
+    output: Vec<u8>,
+    flushes: usize,
+}
+
+impl Write for FlushCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_output_flushed_once_per_run() {
+    let program = format!("+{}", ".".repeat(100_000));
+    let count_flushes = |flush_per_write| {
+        let mut counter = FlushCounter::default();
+        let mut executor = Executor::new(Cursor::new(b""), BufWriter::new(&mut counter));
+        executor.set_flush_per_write(flush_per_write);
+        executor.execute(&program).unwrap();
+        drop(executor);
+        assert_eq!(counter.output, vec![1; 100_000]);
+        counter.flushes
+    };
+    assert_eq!(count_flushes(true), 100_000 + 1);
+    assert_eq!(count_flushes(false), 1);
+}
+
+#[test]
+fn test_step_flushes_once_per_run() {
+    let mut counter = FlushCounter::default();
+    let mut executor = Executor::new(Cursor::new(b""), &mut counter);
+    executor.load("+.+.+.").unwrap();
+    while executor.step().unwrap() != StepOutcome::Finished {}
+    drop(executor);
+    assert_eq!(counter.output, vec![1, 2, 3]);
+    assert_eq!(counter.flushes, 1);
+
+    let mut counter = FlushCounter::default();
+    let mut executor = Executor::new(Cursor::new(b""), &mut counter);
+    executor.set_max_steps(Some(2));
+    executor.load("+.>+.").unwrap();
+    executor.step().unwrap();
+    executor.step().unwrap();
+    assert!(matches!(executor.step(), Err(Error::StepLimitExceeded)));
+    assert_eq!(executor.failed_instruction(), Some(&Instruction::Move(1)));
+    drop(executor);
+    assert_eq!(counter.output, vec![1]);
+    assert_eq!(counter.flushes, 1);
+}
+
+#[test]
+fn test_executor_builder() {
+    let mut output = Vec::new();
+    let mut executor = ExecutorBuilder::new()
+        .cell_type::<u16>()
+        .tape_cells(16)
+        .tape_mode(TapeMode::Growable)
+        .eof(EofBehavior::NegativeOne)
+        .flush_per_write(true)
+        .max_steps(Some(1_000))
+        .timeout(Some(Duration::from_secs(10)))
+        .build(Cursor::new(b""), &mut output);
+    executor.execute(">>>>>>>>>>>>>>>>>>>>,+.").unwrap();
+    assert_eq!(executor.pointer(), 20);
+    assert!(executor.memory.len() > 16);
+    assert!(matches!(executor.execute("+[]"), Err(Error::StepLimitExceeded)));
+    drop(executor);
+    assert_eq!(output, vec![0, 0]);
+}
+
+#[test]
+fn test_execute_instructions_twice() {
+    let instructions = parse(SHORTER_HELLO_WORLD).unwrap();
+    let mut output = Vec::new();
+    let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    executor.execute_instructions(&instructions.clone()).unwrap();
+    executor.reset();
+    executor.execute_instructions(&instructions).unwrap();
+    drop(executor);
+    assert_eq!(output, b"Hello, World!Hello, World!");
+}
+
+#[test]
+fn test_cell_overflow_error() {
+    let mut executor = ExecutorBuilder::new()
+        .cell_overflow(CellOverflow::Error)
+        .build(Cursor::new(b""), Vec::new());
+    let program = format!(">>{}", "+".repeat(256));
+    assert!(matches!(executor.execute(&program), Err(Error::CellOverflow { index: 2 })));
+
+    executor.reset();
+    assert!(matches!(executor.execute(">-"), Err(Error::CellOverflow { index: 1 })));
+    executor.reset();
+    assert!(matches!(executor.execute("++++++++++++++++[->++++++++++++++++<]"), Err(Error::CellOverflow { index: 1 })));
+    executor.reset();
+    assert!(matches!(executor.execute(&format!(">{}<++[->+<]", "+".repeat(255))), Err(Error::CellOverflow { index: 1 })));
+
+    // Clearing a cell and moving it out of its origin is never an overflow.
+    executor.reset();
+    executor.execute("+[+]+++[->>+<<]>>[->++<]").unwrap();
+    assert_eq!(executor.memory[3].0, 6);
+}
+
+#[test]
+fn test_cell_overflow_wraps_by_default() {
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    executor.execute(&format!("{}>-", "+".repeat(256))).unwrap();
+    assert_eq!(executor.memory[0].0, 0);
+    assert_eq!(executor.memory[1].0, 255);
+}
+
+#[test]
+fn test_set_cells() {
+    let mut output = Vec::new();
+    let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    executor.set_cells(0, &[72, 73]).unwrap();
+    executor.execute(".>.").unwrap();
+    assert!(matches!(executor.set_cells(MEMORY_SIZE - 1, &[1, 2]), Err(Error::PointerOutOfBounds { index }) if index == MEMORY_SIZE as isize));
+    assert!(matches!(executor.set_cells(MEMORY_SIZE + 5, &[]), Err(Error::PointerOutOfBounds { index }) if index == (MEMORY_SIZE + 5) as isize));
+    assert_eq!(executor.memory[MEMORY_SIZE - 1].0, 0);
+    executor.set_cells(MEMORY_SIZE - 2, &[1, 2]).unwrap();
+    assert_eq!(executor.dump_nonzero(), vec![(0, 72), (1, 73), (MEMORY_SIZE - 2, 1), (MEMORY_SIZE - 1, 2)]);
+    drop(executor);
+    assert_eq!(&output, b"HI");
+}
+
+/// Input stream setting a stop flag when it is read, as a Ctrl-C pressed while the program runs.
+pub(super) struct StopOnRead(pub(super) Arc<AtomicBool>);
+
+impl Read for StopOnRead {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.store(true, Ordering::Relaxed);
+        buf[0] = 1;
+        Ok(1)
+    }
+}
+
+#[test]
+fn test_stop_flag_interrupts() {
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let mut executor = Executor::new(StopOnRead(stop_flag.clone()), Vec::new());
+    executor.set_stop_flag(Some(stop_flag.clone()));
+    // The step limit only fails the test instead of hanging it if the flag is ignored.
+    executor.set_max_steps(Some(1_000_000));
+    assert!(matches!(executor.execute("+[>+<]"), Err(Error::StepLimitExceeded)));
+    assert!(matches!(executor.execute(",[>+<]"), Err(Error::Interrupted)));
+    stop_flag.store(false, Ordering::Relaxed);
+    executor.execute("+").unwrap();
+}
+
+#[test]
+fn test_debug_dump() {
+    let mut output = Vec::new();
+    let mut executor = Executor::new(Cursor::new(b""), &mut output);
+    executor.execute("++>+++#.").unwrap();
+    assert_eq!(executor.debug_dump(), "pointer 1: 0:2 [1:3] 2:0 3:0 4:0 5:0 6:0 7:0 8:0 9:0");
+    executor.execute(&format!("{}+#", ">".repeat(20))).unwrap();
+    assert_eq!(executor.debug_dump(), "pointer 21: 13:0 14:0 15:0 16:0 17:0 18:0 19:0 20:0 [21:1] 22:0 23:0 24:0 25:0 26:0 27:0 28:0 29:0");
+
+    executor.reset();
+    let instructions = parse_with_options("++>+++#.", ParseOptions { debug: true, ..ParseOptions::default() }).unwrap();
+    assert!(instructions.contains(&Instruction::DebugDump));
+    executor.execute_instructions(&instructions).unwrap();
+    let profile = executor.execute_profiled("#").unwrap();
+    assert_eq!(profile.debug_dumps, 0);
+    drop(executor);
+    assert_eq!(output, vec![3, 3]);
+}
+
+#[test]
+fn test_add_at_matches_naive_executor() {
+    let program = ">+>+<<,[>+>>+++<<<-.]>>->>++<<<<+>+<-<";
+    let instructions = parse(program).unwrap();
+    assert_eq!(instructions[..2], [Instruction::AddAt { offset: 1, value: 1 }, Instruction::AddAt { offset: 2, value: 1 }]);
+
+    let (mut naive_output, mut output) = (Vec::new(), Vec::new());
+    let mut naive = Executor::new(Cursor::new(b"\x03"), &mut naive_output);
+    naive.set_parse_options(ParseOptions { opt_level: OptLevel::None, ..ParseOptions::default() });
+    naive.execute(program).unwrap();
+    let mut executor = Executor::new(Cursor::new(b"\x03"), &mut output);
+    let profile = executor.execute_profiled(program).unwrap();
+    assert_eq!(executor.memory, naive.memory);
+    assert_eq!(executor.pointer(), naive.pointer());
+    assert_eq!(profile.add_ats, 11);
+
+    drop((naive, executor));
+    assert_eq!(output, naive_output);
+
+    // The cells an `AddAt` adds to are accessed, the cells the pointer skips over are not.
+    let mut executor = Executor::new(Cursor::new(b""), Vec::new());
+    let accesses = executor.execute_traced(">>+>>+<<<<").unwrap();
+    assert_eq!(&accesses[..5], &[0, 0, 1, 0, 1]);
+}
@@ -0,0 +1,22 @@
+use crate::executor::{EofBehavior, Executor};
+use crate::input::KeyInput;
+use std::io::{Error, ErrorKind};
+
+#[test]
+fn test_key_input_feeds_keypresses() {
+    let keys = KeyInput::new(vec!['w', 'ñ', 'a', 's', 'd'].into_iter().map(Ok));
+    let mut output = Vec::new();
+    let mut executor = Executor::new(keys, &mut output);
+    executor.set_eof_behavior(EofBehavior::Zero);
+    executor.execute(",[.,]").unwrap();
+    drop(executor);
+    assert_eq!(&output, b"wasd");
+}
+
+#[test]
+fn test_key_input_error() {
+    let keys = KeyInput::new(vec![Ok('a'), Err(Error::new(ErrorKind::NotConnected, "no terminal"))].into_iter());
+    let mut executor = Executor::new(keys, Vec::new());
+    let err = executor.execute(",.,.").unwrap_err();
+    assert_eq!(err.to_string(), "Cannot read or write while running the script: no terminal");
+}
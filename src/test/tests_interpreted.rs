@@ -1,7 +1,8 @@
 use crate::executor::Executor;
+use crate::MEMORY_SIZE;
 use crate::test::MANDELBROT_RESULT;
 use crate::test::scripts::{
-    ADD, COMMENTED_HELLO_WORLD, HELLO_WORLD, MANDELBROT, SHORTER_HELLO_WORLD,
+    ADD, ADD_TO, CLEAR_SCAN, COMMENTED_HELLO_WORLD, HELLO_WORLD, MANDELBROT, MULTIPLY, SEEK, SHORTER_HELLO_WORLD,
 };
 use std::io::stdin;
 
@@ -41,3 +42,81 @@ fn test_mandelbrot() {
     let output = execute_with_output(MANDELBROT);
     assert_eq!(&output, MANDELBROT_RESULT);
 }
+
+#[test]
+fn test_clear_scan() {
+    let output = execute_with_output(CLEAR_SCAN);
+    assert_eq!(&output, "\0\0\0\u{1}");
+}
+
+#[test]
+fn test_multiply() {
+    let output = execute_with_output(MULTIPLY);
+    assert_eq!(&output, "\u{8}\u{9}");
+}
+
+#[test]
+fn test_add_to() {
+    let output = execute_with_output(ADD_TO);
+    assert_eq!(&output, "\u{2}\0\u{5}");
+}
+
+#[test]
+fn test_seek() {
+    let output = execute_with_output(SEEK);
+    assert_eq!(&output, "\u{4}\0");
+}
+
+/// Reference interpreter running the source character by character, without any optimization.
+fn reference_output(program: &str) -> String {
+    let code: Vec<char> = program.chars().filter(|c| "+-<>[].".contains(*c)).collect();
+    let (mut memory, mut index, mut pc, mut output) = (vec![0u8; MEMORY_SIZE], 0, 0, vec![]);
+    while pc < code.len() {
+        match code[pc] {
+            '+' => memory[index] = memory[index].wrapping_add(1),
+            '-' => memory[index] = memory[index].wrapping_sub(1),
+            '>' => index = (index + 1) % MEMORY_SIZE,
+            '<' => index = (index + MEMORY_SIZE - 1) % MEMORY_SIZE,
+            '.' => output.push(memory[index]),
+            '[' if memory[index] == 0 => {
+                let mut depth = 1;
+                while depth > 0 {
+                    pc += 1;
+                    depth += match code[pc] { '[' => 1, ']' => -1, _ => 0 };
+                }
+            }
+            ']' if memory[index] != 0 => {
+                let mut depth = 1;
+                while depth > 0 {
+                    pc -= 1;
+                    depth += match code[pc] { ']' => 1, '[' => -1, _ => 0 };
+                }
+            }
+            _ => {}
+        }
+        pc += 1;
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_matches_reference_interpreter() {
+    // Mandelbrot is left out, it takes too long without optimizations; its output is checked above.
+    for program in [HELLO_WORLD, SHORTER_HELLO_WORLD, COMMENTED_HELLO_WORLD, ADD, CLEAR_SCAN, MULTIPLY, ADD_TO, SEEK] {
+        assert_eq!(execute_with_output(program), reference_output(program));
+    }
+}
+
+#[test]
+fn test_deeply_nested_loops() {
+    let depth = 10_000;
+    let program = format!("+{}-{}+.", "[".repeat(depth), "]".repeat(depth));
+    assert_eq!(execute_with_output(&program), "\u{1}");
+}
+
+#[test]
+fn test_deeply_nested_empty_loops() {
+    let depth = 50_000;
+    let program = format!("{}{}+.", "[".repeat(depth), "]".repeat(depth));
+    assert_eq!(execute_with_output(&program), "\u{1}");
+}
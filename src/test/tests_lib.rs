@@ -0,0 +1,155 @@
+use crate::error::{Error, ParserError};
+use crate::test::scripts::{ADD_TO, HELLO_WORLD, MULTIPLY, SHORTER_HELLO_WORLD};
+use crate::{run, run_capture, run_interpreted, run_single, run_with_embedded_input, transpile_c, validate, MEMORY_SIZE};
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_run_single_forward() {
+    let tape = run_single::<8>(3);
+    assert_eq!(tape, [0, 0, 0, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_run_single_wraps() {
+    let tape = run_single::<MEMORY_SIZE>(-2);
+    assert_eq!(tape[MEMORY_SIZE - 2], 1);
+    assert_eq!(tape.iter().map(|&cell| cell as usize).sum::<usize>(), 1);
+}
+
+
+#[test]
+fn test_run_interpreted_hello_world() {
+    let mut output = Vec::new();
+    run_interpreted(SHORTER_HELLO_WORLD, Cursor::new(b""), &mut output).unwrap();
+    assert_eq!(&output, b"Hello, World!");
+}
+
+#[test]
+fn test_run_hello_world() {
+    let mut output = Vec::new();
+    run(SHORTER_HELLO_WORLD, Cursor::new(b""), &mut output).unwrap();
+    assert_eq!(&output, b"Hello, World!");
+}
+
+#[cfg(any(target_arch="x86_64", target_arch="aarch64"))]
+#[test]
+fn test_run_matches_compiled() {
+    let mut compiled = Vec::new();
+    crate::compiler::compile(HELLO_WORLD, &mut Cursor::new(b""), &mut compiled).unwrap().run().unwrap();
+    let mut output = Vec::new();
+    run(HELLO_WORLD, Cursor::new(b""), &mut output).unwrap();
+    assert_eq!(output, compiled);
+}
+
+#[test]
+fn test_run_capture() {
+    assert_eq!(run_capture(SHORTER_HELLO_WORLD, b"").unwrap(), b"Hello, World!");
+    assert_eq!(run_capture(",+.,+.", b"@A").unwrap(), b"AB");
+    assert!(matches!(run_capture(",.,.", b"a"), Err(Error::RuntimeError(_))));
+    assert!(matches!(run_capture("[", b""), Err(Error::ParseError(ParserError::IncompleteLoop { .. }))));
+}
+
+#[test]
+fn test_run_parse_error() {
+    let err = run("+]", Cursor::new(b""), Vec::new()).unwrap_err();
+    assert!(matches!(err, Error::ParseError(ParserError::UnexpectedToken { position: 1, .. })));
+}
+
+#[test]
+fn test_run_with_embedded_input() {
+    let mut output = Vec::new();
+    run_with_embedded_input(",.!A", Cursor::new(b"B"), &mut output).unwrap();
+    run_with_embedded_input(",.", Cursor::new(b"B"), &mut output).unwrap();
+    assert_eq!(&output, b"AB");
+}
+
+/// Function to transpile a Brainfuck program to C, build it with `cc` and run it with the given
+/// input, returning its output, or `None` if there is no C compiler.
+///
+/// The exit status is not checked, the C program fails at the end of the input like `run` does.
+fn run_transpiled(source: &str, name: &str, input: &[u8]) -> Option<Vec<u8>> {
+    if Command::new("cc").arg("--version").output().is_err() {
+        return None;
+    }
+    let dir = std::env::temp_dir();
+    let c_path = dir.join(format!("headache_test_{name}_{}.c", std::process::id()));
+    let binary_path = dir.join(format!("headache_test_{name}_{}", std::process::id()));
+    std::fs::write(&c_path, transpile_c(source).unwrap()).unwrap();
+    let status = Command::new("cc").arg("-O2").arg(&c_path).arg("-o").arg(&binary_path).status().unwrap();
+    assert!(status.success());
+    let mut child = Command::new(&binary_path).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&c_path).unwrap();
+    std::fs::remove_file(&binary_path).unwrap();
+    Some(output.stdout)
+}
+
+#[test]
+fn test_transpile_c_hello_world() {
+    let Some(output) = run_transpiled(HELLO_WORLD, "hello_world", b"") else {
+        return;
+    };
+    assert_eq!(&output, b"Hello World!\n");
+}
+
+#[test]
+fn test_transpile_c_matches_run() {
+    // The input read first keeps the loops from being folded into constant output.
+    let sources = [
+        format!(",[-]{HELLO_WORLD}"),
+        format!(",[-]{ADD_TO}"),
+        format!(",[-]{MULTIPLY}"),
+        ",[.,]".to_string(),
+        ",<<+++++[>>+++++++++++++<<-]>>.[>]<<.".to_string(),
+    ];
+    for (i, source) in sources.iter().enumerate() {
+        let input = b"abc";
+        let Some(output) = run_transpiled(source, &format!("matches_run_{i}"), input) else {
+            return;
+        };
+        let mut expected = Vec::new();
+        let _ = run(source, Cursor::new(input), &mut expected);
+        assert_eq!(output, expected, "{source}");
+    }
+}
+
+#[test]
+fn test_validate_hello_world() {
+    let stats = validate(HELLO_WORLD).unwrap();
+    assert!(!stats.has_reads());
+    assert_eq!(stats.writes, 13);
+    // The inner loop becomes a `MultiplyInto`, and the `[<]` makes the extent depend on the data.
+    assert_eq!(stats.max_loop_depth, 1);
+    assert_eq!(stats.tape_extent, None);
+}
+
+#[test]
+fn test_validate_echo() {
+    let stats = validate(",[.,]").unwrap();
+    assert!(stats.has_reads());
+    assert_eq!((stats.reads, stats.writes), (2, 1));
+    assert_eq!(stats.instructions, 4);
+    assert_eq!(stats.tape_extent, Some((0, 0)));
+
+    let stats = validate("<<[>>>,[-]<]").unwrap();
+    assert_eq!(stats.max_loop_depth, 1);
+    assert_eq!(stats.tape_extent, None);
+
+    let stats = validate("+[>>[<.>-]++[->>+<<]<<-]").unwrap();
+    assert_eq!(stats.max_loop_depth, 2);
+    assert_eq!(stats.tape_extent, Some((0, 4)));
+
+    assert!(matches!(validate(",[."), Err(Error::ParseError(ParserError::IncompleteLoop { .. }))));
+}
+
+#[test]
+fn test_validate_add_at_extent() {
+    let stats = validate(">+>+<<").unwrap();
+    assert_eq!(stats.instructions, 3);
+    assert_eq!(stats.tape_extent, Some((0, 2)));
+
+    let stats = validate("<<+>>>+<[->+<<<->>]").unwrap();
+    assert_eq!(stats.tape_extent, Some((-2, 1)));
+}
@@ -0,0 +1,19 @@
+use crate::lint::{lint, Warning};
+
+#[test]
+fn test_dead_write() {
+    let warnings = lint("+++>++++++.").unwrap();
+    assert_eq!(warnings, vec![Warning::DeadWrite { position: 0 }]);
+}
+
+#[test]
+fn test_write_overwritten_by_read() {
+    let warnings = lint("++,.").unwrap();
+    assert_eq!(warnings, vec![Warning::DeadWrite { position: 0 }]);
+}
+
+#[test]
+fn test_writes_observed_by_loops() {
+    assert!(lint("+++>++[<.>-]").unwrap().is_empty());
+    assert!(lint("++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]").unwrap().is_empty());
+}
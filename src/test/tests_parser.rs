@@ -0,0 +1,342 @@
+use crate::error::{BytecodeError, ParserError};
+use crate::instruction::{drop_instructions, Instruction};
+use crate::executor::Executor;
+use crate::parser::{check_brackets, fold_constant_output, format_instructions, from_bytecode, parse, parse_with_input, parse_with_options, to_bytecode, OptLevel, ParseOptions};
+use crate::test::scripts::{COMMENTED_HELLO_WORLD, HELLO_WORLD, SHORTER_HELLO_WORLD};
+use std::io::Cursor;
+
+#[test]
+fn test_clear_scan() {
+    let instructions = parse("[[-]>]").unwrap();
+    assert_eq!(instructions, vec![Instruction::ClearScan { stride: 1 }]);
+}
+
+#[test]
+fn test_clear_scan_requires_clear_then_move() {
+    let instructions = parse("[>[-]]").unwrap();
+    assert_eq!(instructions, vec![Instruction::Loop(vec![Instruction::Move(1), Instruction::Clear])]);
+}
+
+#[test]
+fn test_decrements_fold_into_previous_add() {
+    assert_eq!(parse("+--").unwrap(), vec![Instruction::Add(255)]);
+    assert_eq!(parse("-----").unwrap(), vec![Instruction::Add(251)]);
+}
+
+#[test]
+fn test_multiply_loop() {
+    let instructions = parse("[->+++>++<<]").unwrap();
+    assert_eq!(instructions, vec![Instruction::MultiplyInto { targets: vec![(1, 3), (2, 2)] }]);
+}
+
+#[test]
+fn test_single_copy_loop_is_add_to() {
+    assert_eq!(parse("[>+<-]").unwrap(), vec![Instruction::AddTo { offset: 1 }]);
+}
+
+#[test]
+fn test_overflowing_delta_loop_is_not_multiply() {
+    assert!(matches!(parse(&format!("[{}>+<]", "+".repeat(255))).unwrap()[..], [Instruction::Loop(_)]));
+    assert!(matches!(parse(&format!("[->{}<]", "+".repeat(200))).unwrap()[..], [Instruction::Loop(_)]));
+}
+
+#[test]
+fn test_unbalanced_loop_is_not_multiply() {
+    let instructions = parse("[->+]").unwrap();
+    assert_eq!(instructions, vec![Instruction::Loop(vec![Instruction::Add(255), Instruction::Move(1), Instruction::Add(1)])]);
+}
+
+#[test]
+fn test_seek() {
+    assert_eq!(parse("[>]").unwrap(), vec![Instruction::Seek { stride: 1 }]);
+    assert_eq!(parse("[<<]").unwrap(), vec![Instruction::Seek { stride: -2 }]);
+}
+
+#[test]
+fn test_clear_then_add_is_set() {
+    assert_eq!(parse("[-]+++++").unwrap(), vec![Instruction::Set(5)]);
+    assert_eq!(parse("[-]--").unwrap(), vec![Instruction::Set(254)]);
+    assert_eq!(parse("+[-]+++.[-]").unwrap(), vec![Instruction::Set(3), Instruction::Write, Instruction::Clear]);
+    // A move between them keeps the clear and the add apart.
+    assert_eq!(parse("[-]>+").unwrap(), vec![Instruction::Clear, Instruction::Move(1), Instruction::Add(1)]);
+    assert_eq!(format_instructions(&parse("[-]+++++").unwrap()), "Set +5\n");
+    // The same byte is shown the same way whatever instruction holds it.
+    assert_eq!(format_instructions(&parse("[-]-<-[->-<]").unwrap()), "Set -1\nMove -1\nAdd -1\nMultiplyInto +1 x-1\n");
+    let instructions = parse("[-]+++++.").unwrap();
+    assert_eq!(from_bytecode(&to_bytecode(&instructions)).unwrap(), instructions);
+}
+
+#[test]
+fn test_error_line_and_column() {
+    let source = "++++\n[>+\n  <-]]\n.";
+    let err = parse(source).unwrap_err();
+    assert_eq!(err, ParserError::UnexpectedToken { position: 14, line: 3, col: 6 });
+    assert_eq!(err.caret(source), "  <-]]\n     ^");
+
+    let source = "+\n>[-\n<[+]";
+    let err = parse(source).unwrap_err();
+    assert_eq!(err, ParserError::IncompleteLoop { position: 3, line: 2, col: 2 });
+    assert_eq!(err.caret(source), ">[-\n ^");
+}
+
+#[test]
+fn test_check_brackets() {
+    assert_eq!(check_brackets(""), Ok(()));
+    assert_eq!(check_brackets("+[>[-]<[.]]\n[,]"), Ok(()));
+    // The open bracket reported is the innermost one.
+    assert_eq!(check_brackets("[\n+[[-]"), Err(ParserError::IncompleteLoop { position: 3, line: 2, col: 2 }));
+    assert_eq!(check_brackets("[-]]["), Err(ParserError::UnexpectedToken { position: 3, line: 1, col: 4 }));
+    assert_eq!(check_brackets("+\n]["), Err(ParserError::UnexpectedToken { position: 2, line: 2, col: 1 }));
+    // The errors match the ones of the parser.
+    for source in ["[\n+[[-]", "[-]]["] {
+        assert_eq!(check_brackets(source).unwrap_err(), parse(source).unwrap_err());
+    }
+}
+
+#[test]
+fn test_bytecode_round_trip() {
+    let instructions = parse("+++[->>+<<]>[<[[-]>]>[>>]-[->+++>++<<]],.[<<<]").unwrap();
+    assert!(instructions.iter().any(|i| matches!(i, Instruction::AddTo { .. })));
+    assert_eq!(from_bytecode(&to_bytecode(&instructions)).unwrap(), instructions);
+
+    let nested = vec![Instruction::Loop(vec![
+        Instruction::Move(-20_000),
+        Instruction::Loop(vec![Instruction::AddTo { offset: isize::MIN }, Instruction::Seek { stride: isize::MAX }]),
+        Instruction::MultiplyInto { targets: vec![(-1, 255), (64, 3)] },
+    ])];
+    assert_eq!(from_bytecode(&to_bytecode(&nested)).unwrap(), nested);
+}
+
+#[test]
+fn test_bytecode_errors() {
+    let bytecode = to_bytecode(&parse("+[>[-]<-]").unwrap());
+    assert_eq!(
+        from_bytecode(&bytecode[..bytecode.len() - 1]),
+        Err(BytecodeError::UnexpectedEnd { position: bytecode.len() - 1 })
+    );
+    assert_eq!(from_bytecode(&[1]), Err(BytecodeError::UnexpectedEnd { position: 1 }));
+    assert_eq!(from_bytecode(&[2, 5]), Err(BytecodeError::InvalidOpcode { opcode: 5, position: 1 }));
+    assert_eq!(from_bytecode(&[0xff]), Err(BytecodeError::InvalidOpcode { opcode: 0xff, position: 0 }));
+    assert_eq!(from_bytecode(&[]), Ok(vec![]));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_json_round_trip() {
+    let instructions = parse("+[->>+<<]>[[-]>]").unwrap();
+    let json = serde_json::to_string(&instructions).unwrap();
+    assert_eq!(json, r#"[{"Add":1},{"AddTo":{"offset":2}},{"Move":1},{"ClearScan":{"stride":1}}]"#);
+    assert_eq!(serde_json::from_str::<Vec<Instruction>>(&json).unwrap(), instructions);
+}
+
+#[test]
+fn test_parse_with_input() {
+    let (instructions, input) = parse_with_input(",.!A").unwrap();
+    assert_eq!(instructions, vec![Instruction::Read, Instruction::Write]);
+    assert_eq!(input, b"A");
+
+    // The input is not Brainfuck, even if it looks like it.
+    assert_eq!(parse_with_input("+\\!.![").unwrap(), (vec![Instruction::Add(1), Instruction::Write], b"[".to_vec()));
+    assert_eq!(parse_with_input("+[-]").unwrap(), (parse("+[-]").unwrap(), vec![]));
+    assert_eq!(parse("+!.").unwrap(), vec![Instruction::Add(1), Instruction::Write]);
+}
+
+#[test]
+fn test_debug_dump() {
+    let debug = ParseOptions { debug: true, ..ParseOptions::default() };
+    assert_eq!(parse("+#+.[#-]").unwrap(), vec![Instruction::Add(2), Instruction::Write, Instruction::Clear]);
+    let instructions = parse_with_options("+#+.[#-]", debug).unwrap();
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction::Add(1),
+            Instruction::DebugDump,
+            Instruction::Add(1),
+            Instruction::Write,
+            Instruction::Loop(vec![Instruction::DebugDump, Instruction::Add(255)]),
+        ]
+    );
+    assert_eq!(from_bytecode(&to_bytecode(&instructions)).unwrap(), instructions);
+}
+
+#[test]
+fn test_dead_store_elimination() {
+    assert_eq!(parse("+++[-]").unwrap(), vec![Instruction::Clear]);
+    assert_eq!(parse("[-]+").unwrap(), vec![Instruction::Set(1)]);
+    assert_eq!(parse("[-][+]").unwrap(), vec![Instruction::Clear]);
+    assert_eq!(parse("[-]+[-]").unwrap(), vec![Instruction::Clear]);
+    // The store is dead across moves, and the moves around it are folded again.
+    assert_eq!(parse("+>++<>[-]<.").unwrap(), vec![Instruction::Add(1), Instruction::Move(1), Instruction::Clear, Instruction::Move(-1), Instruction::Write]);
+    assert_eq!(
+        parse("++>+<[-]>.").unwrap(),
+        vec![Instruction::AddAt { offset: 1, value: 1 }, Instruction::Move(0), Instruction::Clear, Instruction::Move(1), Instruction::Write]
+    );
+    // Stores used by an output, a loop or anything else are kept.
+    assert_eq!(parse("+.[-]").unwrap(), vec![Instruction::Add(1), Instruction::Write, Instruction::Clear]);
+    assert_eq!(parse("+,[-]").unwrap(), vec![Instruction::Add(1), Instruction::Read, Instruction::Clear]);
+    assert_eq!(parse("+[>]<[-]").unwrap(), vec![Instruction::Add(1), Instruction::Seek { stride: 1 }, Instruction::Move(-1), Instruction::Clear]);
+    assert_eq!(parse("+>+").unwrap(), vec![Instruction::Add(1), Instruction::Move(1), Instruction::Add(1)]);
+    assert_eq!(parse("+[++[-]-]").unwrap(), vec![Instruction::Add(1), Instruction::Loop(vec![Instruction::Set(255)])]);
+}
+
+#[test]
+fn test_fold_constant_output() {
+    let instructions = fold_constant_output(parse("++++++++[>++++++++<-]>+.+.>++[-]+++.").unwrap());
+    assert_eq!(instructions[0], Instruction::WriteConst(vec![b'A', b'B', 3]));
+    assert!(!instructions.iter().any(|i| matches!(i, Instruction::Write)));
+
+    for program in ["++++++++[>++++++++<-]>+.+.>++[-]+++.", HELLO_WORLD, SHORTER_HELLO_WORLD, COMMENTED_HELLO_WORLD] {
+        let instructions = fold_constant_output(parse(program).unwrap());
+
+        let (mut folded, mut original) = (Vec::new(), Vec::new());
+        let mut executor = Executor::new(Cursor::new(b""), &mut folded);
+        executor.execute_instructions(&instructions).unwrap();
+        let memory = executor.memory.clone();
+        let pointer = executor.pointer();
+        let mut executor = Executor::new(Cursor::new(b""), &mut original);
+        executor.execute(program).unwrap();
+        assert_eq!((&memory, pointer), (&executor.memory, executor.pointer()));
+        drop(executor);
+        assert_eq!(folded, original);
+    }
+}
+
+#[test]
+fn test_fold_constant_output_stops_at_input() {
+    let instructions = fold_constant_output(parse("++.>+<[->+<],.").unwrap());
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction::WriteConst(vec![2]),
+            Instruction::Move(1),
+            Instruction::Add(3),
+            Instruction::Move(-1),
+            Instruction::Read,
+            Instruction::Write,
+        ]
+    );
+    // Loops are never evaluated, even the ones that would finish, the output before them is.
+    let instructions = fold_constant_output(parse("+.+[]").unwrap());
+    assert_eq!(instructions, vec![Instruction::WriteConst(vec![1]), Instruction::Add(2), Instruction::Loop(vec![])]);
+    let instructions = fold_constant_output(parse("+.[-.]+.").unwrap());
+    assert_eq!(instructions, vec![
+        Instruction::WriteConst(vec![1]),
+        Instruction::Add(1),
+        Instruction::Loop(vec![Instruction::Add(255), Instruction::Write]),
+        Instruction::Add(1),
+        Instruction::Write,
+    ]);
+    assert_eq!(fold_constant_output(parse("+.>+[>]+.").unwrap())[4], Instruction::Seek { stride: 1 });
+    // Without any output the program is not changed.
+    assert_eq!(fold_constant_output(parse("+[>+<-],.").unwrap()), parse("+[>+<-],.").unwrap());
+    let instructions = parse("+.#.").unwrap();
+    assert_eq!(from_bytecode(&to_bytecode(&fold_constant_output(instructions))).unwrap(), vec![Instruction::WriteConst(vec![1, 1]), Instruction::Add(1)]);
+}
+
+#[test]
+fn test_opt_levels() {
+    let level = |opt_level| ParseOptions { opt_level, ..ParseOptions::default() };
+    assert_eq!(parse_with_options("+++", level(OptLevel::None)).unwrap(), vec![Instruction::Add(1); 3]);
+    assert_eq!(parse_with_options("+++", level(OptLevel::Fold)).unwrap(), vec![Instruction::Add(3)]);
+    assert_eq!(parse_with_options("+++", level(OptLevel::Full)).unwrap(), vec![Instruction::Add(3)]);
+    assert_eq!(
+        parse_with_options(">><[-]", level(OptLevel::None)).unwrap(),
+        vec![
+            Instruction::Move(1),
+            Instruction::Move(1),
+            Instruction::Move(-1),
+            Instruction::Loop(vec![Instruction::Add(255)]),
+        ]
+    );
+    assert_eq!(
+        parse_with_options(">><[-]", level(OptLevel::Fold)).unwrap(),
+        vec![Instruction::Move(1), Instruction::Loop(vec![Instruction::Add(255)])]
+    );
+    assert_eq!(parse_with_options(">><[-]", level(OptLevel::Full)).unwrap(), vec![Instruction::Move(1), Instruction::Clear]);
+    // Dead stores are only removed with every optimization.
+    assert_eq!(
+        parse_with_options("+[-]", level(OptLevel::Fold)).unwrap(),
+        vec![Instruction::Add(1), Instruction::Loop(vec![Instruction::Add(255)])]
+    );
+
+    // Every level runs the same.
+    for program in [HELLO_WORLD, SHORTER_HELLO_WORLD, COMMENTED_HELLO_WORLD] {
+        let mut outputs = vec![];
+        for opt_level in [OptLevel::None, OptLevel::Fold, OptLevel::Full] {
+            let mut output = Vec::new();
+            let mut executor = Executor::new(Cursor::new(b""), &mut output);
+            executor.execute_instructions(&parse_with_options(program, level(opt_level)).unwrap()).unwrap();
+            outputs.push(output);
+        }
+        assert_eq!(outputs[0], outputs[1]);
+        assert_eq!(outputs[1], outputs[2]);
+    }
+}
+
+#[test]
+fn test_format_instructions() {
+    assert_eq!(format_instructions(&parse("++[->+<]").unwrap()), "Add +2\nAddTo +1\n");
+    let level = |opt_level| ParseOptions { opt_level, ..ParseOptions::default() };
+    assert_eq!(
+        format_instructions(&parse_with_options("++[->+<]", level(OptLevel::Fold)).unwrap()),
+        "Add +2\nLoop\n    Add -1\n    Move +1\n    Add +1\n    Move -1\n",
+    );
+    assert_eq!(
+        format_instructions(&parse(",[>[-]<[>>+++>+<<<-]>>[<]<<.,]").unwrap()),
+        "Read\nLoop\n    Move +1\n    Clear\n    Move -1\n    MultiplyInto +2 x3, +3 x1\n    Move +2\n    Seek -1\n    Move -2\n    Write\n    Read\n",
+    );
+    assert_eq!(format_instructions(&fold_constant_output(parse("+++[>++++++++<-]>.").unwrap())), "WriteConst \"\\x18\"\nMove +1\nAdd +24\n");
+    assert_eq!(format_instructions(&[]), "");
+}
+
+#[test]
+fn test_fuse_moves() {
+    // The segment ends with its net move, even when it is 0.
+    assert_eq!(
+        parse(">+>+<<").unwrap(),
+        vec![Instruction::AddAt { offset: 1, value: 1 }, Instruction::AddAt { offset: 2, value: 1 }, Instruction::Move(0)]
+    );
+    assert_eq!(parse("+>+>-<").unwrap(), vec![
+        Instruction::Add(1),
+        Instruction::AddAt { offset: 2, value: 255 },
+        Instruction::Move(1),
+        Instruction::Add(1),
+    ]);
+    // The adds to the same cell are folded, and the pointer moves before anything that needs it.
+    assert_eq!(parse(">+<<+>>+<.").unwrap(), vec![
+        Instruction::AddAt { offset: 1, value: 2 },
+        Instruction::AddAt { offset: -1, value: 1 },
+        Instruction::Move(0),
+        Instruction::Write,
+    ]);
+    assert_eq!(
+        parse(">+<[>+<-]").unwrap(),
+        vec![Instruction::AddAt { offset: 1, value: 1 }, Instruction::Move(0), Instruction::AddTo { offset: 1 }]
+    );
+    assert_eq!(parse(">+.").unwrap(), vec![Instruction::Move(1), Instruction::Add(1), Instruction::Write]);
+    assert_eq!(
+        parse(",[>+>++<<.,]").unwrap(),
+        vec![Instruction::Read, Instruction::Loop(vec![
+            Instruction::AddAt { offset: 1, value: 1 },
+            Instruction::AddAt { offset: 2, value: 2 },
+            Instruction::Move(0),
+            Instruction::Write,
+            Instruction::Read,
+        ])]
+    );
+    let options = ParseOptions { opt_level: OptLevel::Fold, ..ParseOptions::default() };
+    assert_eq!(parse_with_options(">+<", options).unwrap(), vec![Instruction::Move(1), Instruction::Add(1), Instruction::Move(-1)]);
+
+    let instructions = parse(">->+<<").unwrap();
+    assert_eq!(format_instructions(&instructions), "AddAt +1 -1\nAddAt +2 +1\nMove +0\n");
+    assert_eq!(from_bytecode(&to_bytecode(&instructions)).unwrap(), instructions);
+}
+
+#[test]
+fn test_drop_deeply_nested_instructions() {
+    let depth = 50_000;
+    let mut instructions = parse(&format!("{}{}", "[".repeat(depth), "]".repeat(depth))).unwrap();
+    // The body of a loop can be moved out of it.
+    let Some(Instruction::Loop(body)) = instructions.pop() else { panic!("no loop") };
+    drop_instructions(body);
+}
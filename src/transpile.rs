@@ -0,0 +1,111 @@
+use crate::bytecode::{lower, Op};
+use crate::instruction::Instruction;
+use crate::MEMORY_SIZE;
+
+/// Function to translate Brainfuck instructions into the source code of a C program, see
+/// `transpile_c`.
+///
+/// The instructions are walked in their flat form, so deeply nested loops cannot overflow the
+/// call stack.
+///
+/// # Arguments
+///
+/// * `instructions` - A slice of `Instruction` values to be translated.
+pub(crate) fn c_program(instructions: &[Instruction]) -> String {
+    let mut c = String::new();
+    c.push_str("#include <stdio.h>\n\n");
+    c.push_str(&format!("static unsigned char mem[{MEMORY_SIZE}];\n\n"));
+    c.push_str("int main(void) {\n");
+    c.push_str("    size_t p = 0;\n");
+    let mut depth = 1;
+    for op in lower(instructions) {
+        match op {
+            Op::JumpIfZero(_) => {
+                push_line(&mut c, depth, "while (mem[p]) {");
+                depth += 1;
+            }
+            Op::JumpIfNonZero(_) => {
+                depth -= 1;
+                push_line(&mut c, depth, "}");
+            }
+            Op::Instruction(instruction) => c_instruction(&instruction, &mut c, depth),
+        }
+    }
+    push_line(&mut c, depth, "return 0;");
+    c.push_str("}\n");
+    c
+}
+
+/// Function to append the C statements of an instruction other than a loop.
+///
+/// Like the object files of `compiler::compile_to_object`, the program exits with status 1 if
+/// a byte cannot be read or written.
+///
+/// # Arguments
+///
+/// * `instruction` - The `Instruction` to be translated.
+/// * `c` - The C source to which the statements will be appended.
+/// * `depth` - The indentation level of the statements.
+fn c_instruction(instruction: &Instruction, c: &mut String, depth: usize) {
+    match instruction {
+        Instruction::Move(delta) => push_line(c, depth, &format!("p = {};", cell_index(*delta))),
+        Instruction::Add(n) => push_line(c, depth, &format!("mem[p] += {n};")),
+        Instruction::AddAt { offset, value } => push_line(c, depth, &format!("mem[{}] += {value};", cell_index(*offset))),
+        Instruction::Write => push_line(c, depth, "if (putchar(mem[p]) == EOF) return 1;"),
+        Instruction::WriteConst(constant) => {
+            let literal: String = constant.iter().map(|byte| format!("\\x{byte:02x}")).collect();
+            let len = constant.len();
+            push_line(c, depth, &format!("if (fwrite(\"{literal}\", 1, {len}, stdout) != {len}) return 1;"));
+        }
+        Instruction::Read => {
+            // The output asked for the input must show up before waiting for it.
+            push_line(c, depth, "fflush(stdout);");
+            push_line(c, depth, "{ int byte = getchar(); if (byte == EOF) return 1; mem[p] = byte; }");
+        }
+        Instruction::Loop(_) => unreachable!("loops are lowered to jumps"),
+        Instruction::Clear => push_line(c, depth, "mem[p] = 0;"),
+        Instruction::Set(n) => push_line(c, depth, &format!("mem[p] = {n};")),
+        Instruction::AddTo { offset } => {
+            push_line(c, depth, &format!("mem[{}] += mem[p];", cell_index(*offset)));
+            push_line(c, depth, "mem[p] = 0;");
+        }
+        Instruction::ClearScan { stride } => {
+            push_line(c, depth, &format!("while (mem[p]) {{ mem[p] = 0; p = {}; }}", cell_index(*stride)));
+        }
+        Instruction::Seek { stride } => push_line(c, depth, &format!("while (mem[p]) p = {};", cell_index(*stride))),
+        Instruction::MultiplyInto { targets } => {
+            for (offset, factor) in targets {
+                push_line(c, depth, &format!("mem[{}] += mem[p] * {factor};", cell_index(*offset)));
+            }
+            push_line(c, depth, "mem[p] = 0;");
+        }
+        Instruction::DebugDump => {
+            push_line(c, depth, "fflush(stdout);");
+            push_line(c, depth, "fprintf(stderr, \"pointer %zu: [%zu:%d]\\n\", p, p, mem[p]);");
+        }
+    }
+}
+
+/// Function to produce the C expression of the index of the cell at `offset` from the data
+/// pointer, wrapping around the ends of the memory array like the `Executor` does.
+///
+/// # Arguments
+///
+/// * `offset` - Offset from the data pointer of the cell.
+fn cell_index(offset: isize) -> String {
+    let offset = offset.rem_euclid(MEMORY_SIZE as isize);
+    if offset == 0 {
+        "p".to_string()
+    } else {
+        format!("(p + {offset}) % {MEMORY_SIZE}")
+    }
+}
+
+/// Function to append an indented line of C source.
+fn push_line(c: &mut String, depth: usize, line: &str) {
+    for _ in 0..depth {
+        c.push_str("    ");
+    }
+    c.push_str(line);
+    c.push('\n');
+}
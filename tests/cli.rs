@@ -0,0 +1,254 @@
+#![cfg(feature = "build-binary")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn headache(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_headache"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn headache_with_stdin(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_headache"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn test_jit_input_string() {
+    let output = headache(&["--jit", "--input-string", "abc", "-e", ",.,.,."]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"abc");
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn test_jit_hello_world() {
+    let output = headache(&["--jit", "-e", "+[-->-[>>+>-----<<]<--<---]>-.>>>+.>>..+++[.>]<<<<.+++.------.<<-.>>>>+."]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"Hello, World!");
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn test_emit_asm() {
+    let output = headache(&["--emit-asm", "-e", "+."]);
+    assert!(output.status.success());
+    let asm = String::from_utf8(output.stdout).unwrap();
+    assert!(asm.contains("add     BYTE [r12 + r13], 1"));
+    assert!(asm.contains("call    QWORD [r14 + 8]"));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn test_emit_asm_parse_error() {
+    let output = headache(&["--emit-asm", "-e", "+["]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: All the '[' instructions must be closed with a ']' instruction (line 1, column 2)"));
+    assert!(!stderr.contains("ParseError"));
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[test]
+fn test_jit_unsupported() {
+    let output = headache(&["--jit", "-e", "+."]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only supported on x86_64 and aarch64"));
+}
+
+#[test]
+fn test_interpreter_input_string() {
+    let output = headache(&["--input-string", "abc", "-e", ",.,.,."]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"abc");
+}
+
+#[test]
+fn test_input_file() {
+    let path = std::env::temp_dir().join("headache_test_input_file.txt");
+    std::fs::write(&path, "xyz").unwrap();
+    let output = headache(&["--input-file", path.to_str().unwrap(), "-e", ",.,.,."]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"xyz");
+}
+
+#[test]
+fn test_missing_input_file() {
+    let path = std::env::temp_dir().join("headache_test_missing_input_file.txt");
+    let _ = std::fs::remove_file(&path);
+    let output = headache(&["--input-file", path.to_str().unwrap(), "-e", ",."]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("Cannot read the input file {}", path.display())));
+    assert!(!stderr.contains("Cannot read the script"));
+}
+
+#[test]
+fn test_max_steps() {
+    let output = headache(&["--max-steps", "1000", "-e", "+[]"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Error: The step limit was exceeded"));
+}
+
+#[test]
+fn test_input_numbers() {
+    let output = headache(&["--input-numbers", "72 105", "-e", ",.,."]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"Hi");
+}
+
+#[test]
+fn test_input_numbers_out_of_range() {
+    let output = headache(&["--input-numbers", "72 300", "-e", ",.,."]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'300'"));
+}
+
+#[test]
+fn test_script_from_stdin() {
+    let output = headache_with_stdin(&["-"], b"++++++++[>++++++++<-]>+.+.");
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"AB");
+}
+
+#[test]
+fn test_script_from_stdin_with_input_string() {
+    let output = headache_with_stdin(&["--input-string", "hi", "-"], b",.,.");
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"hi");
+}
+
+#[test]
+fn test_parse_error_caret() {
+    let output = headache(&["-e", "+++\n>>]<"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("(line 2, column 3)"));
+    assert!(stderr.contains("2 | >>]<\n  |   ^ this ']' has no matching '['\n"));
+    // The standard error of the test is not a terminal, so there are no colors.
+    assert!(!stderr.contains('\x1b'));
+
+    let output = headache(&["-e", "+[\n>[-]"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 | +[\n  |  ^ this '[' is never closed\n"));
+}
+
+#[test]
+fn test_runtime_error_context() {
+    let output = headache(&["--input-string", "", "-e", ">++,"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: Cannot read or write while running the script"));
+    assert!(stderr.contains("while running Read with the data pointer at cell 1 holding 2"));
+}
+
+#[test]
+fn test_repl_session() {
+    let session = b"+++>++\n:dump\n:reset\n[ exit in a comment\n]\n+++.\n:dump\n:help\nexit\n+.";
+    let output = headache_with_stdin(&["-i"], session);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("pointer: 1\n[0] = 3\n[1] = 2\n"));
+    assert!(stdout.contains("\u{3}>pointer: 0\n[0] = 3\n"));
+    assert!(stdout.contains(":reset  Zero the memory"));
+    assert!(!stdout.contains('\u{4}'));
+}
+
+#[test]
+fn test_repl_unmatched_bracket() {
+    // An extra ']' is reported at once instead of waiting for more lines.
+    let output = headache_with_stdin(&["-i"], b"+]\n+++.\nexit\n");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 | +]\n  |  ^ this ']' has no matching '['\n"));
+    assert!(output.stdout.ends_with(b">\x03>"));
+}
+
+#[test]
+fn test_repl_block() {
+    // The lines of a block only run at `:end`, even if they are balanced on their own.
+    let session = b"+\n:begin\n++++\n[>+\n<-]\n>.\n:end\n:dump\n:begin\n+[\n:end\n:dump\nexit\n";
+    let output = headache_with_stdin(&["-i"], session);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(">>==>==>==>==>==>\u{5}>pointer: 1\n[1] = 5\n"));
+    // A loop left open at `:end` is reported instead of waiting for more lines, and nothing runs.
+    assert!(stdout.ends_with(">==>==>>pointer: 1\n[1] = 5\n>"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 | +[\n  |  ^ this '[' is never closed\n"));
+}
+
+#[test]
+fn test_split_input() {
+    let output = headache(&["--split-input", "-e", ",.!A"]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"A");
+
+    let output = headache_with_stdin(&["--split-input", "-e", ",."], b"B");
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"B");
+}
+
+#[test]
+fn test_opt_levels() {
+    let hello_world = "+[-->-[>>+>-----<<]<--<---]>-.>>>+.>>..+++[.>]<<<<.+++.------.<<-.>>>>+.";
+    for level in ["-O0", "-O1", "-O2"] {
+        let output = headache(&[level, "-e", hello_world]);
+        assert!(output.status.success());
+        assert_eq!(&output.stdout, b"Hello, World!");
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        {
+            let output = headache(&[level, "--jit", "-e", hello_world]);
+            assert!(output.status.success());
+            assert_eq!(&output.stdout, b"Hello, World!");
+        }
+    }
+
+    // Without optimizations every command and every iteration of the loop is a step.
+    assert!(headache(&["-O2", "--max-steps", "5", "-e", "+++++[-]"]).status.success());
+    assert!(!headache(&["-O0", "--max-steps", "5", "-e", "+++++[-]"]).status.success());
+
+    assert!(!headache(&["-O3", "-e", "+"]).status.success());
+}
+
+#[test]
+fn test_check() {
+    let output = headache(&["--check", "-e", ",[.,]"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Instructions: 4\nMax loop depth: 1\nReads: 2\nWrites: 1\nTape extent: +0 to +0\n"
+    );
+
+    let output = headache(&["--check", "--input-string", "ignored", "-e", "+[.>]"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("Tape extent: unknown\n"));
+
+    let output = headache(&["--check", "-e", "+[."]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("this '[' is never closed"));
+}
+
+#[test]
+fn test_dump_ir() {
+    let output = headache(&["--dump-ir", "-e", "++[->+<]"]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"Add +2\nAddTo +1\n");
+
+    let output = headache(&["--dump-ir", "-O1", "-e", "[-]"]);
+    assert!(output.status.success());
+    assert_eq!(&output.stdout, b"Loop\n    Add -1\n");
+
+    let output = headache(&["--dump-ir", "-e", "[-"]);
+    assert!(!output.status.success());
+}